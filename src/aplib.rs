@@ -1,15 +1,95 @@
+use std::borrow::Cow;
+
 use crate::{
-    error::Error::{AplibDecompressInvalidheaderError, AplibDecompressShortHeaderError},
+    depack,
+    error::Error::{
+        AplibDecompressInvalidheaderError, AplibDecompressShortHeaderError, AplibVerifyError,
+    },
     result::Result,
 };
-pub fn compress(input: &[u8]) -> Result<Vec<u8>>
+
+// Definition format identifier for 0xFFFF prefixed aPLib data.
+pub const FORMAT: &str = "ff6-aplib";
+
+// Named apultra tuning selected globally or per definition entry.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Profile
 {
-    let window_size = 0x10000;
-    let dictionary_size = 0;
-    let flags = 0;
-    let progress = None;
+    pub name:       String,
+    pub window:     usize, // apultra window size.
+    pub max_offset: usize, // Largest match offset, bounding the decoder's cost on SNES.
+    pub flags:      u32,   // apultra flags.
+}
+
+impl Default for Profile
+{
+    fn default() -> Profile
+    {
+        Profile {
+            name:       "default".into(),
+            window:     0x10000,
+            max_offset: 0x10000,
+            flags:      0,
+        }
+    }
+}
+
+impl Profile
+{
+    // Look up a built-in profile. The aPLib decoder takes a longer path for match offsets of
+    // 32000 and above ('fast') and of 1280 and above ('near').
+    pub fn builtin(name: &str) -> Option<Profile>
+    {
+        let (window, max_offset) = match name
+        {
+            | "default" => (0x10000, 0x10000),
+            | "fast" => (0x10000, 0x7CFF),
+            | "near" => (0x10000, 0x4FF),
+            | _ => return None,
+        };
+        Some(Profile { name: name.into(), window, max_offset, flags: 0 })
+    }
+
+    // apultra only emits matches within the window, so the smaller of the two bounds the offset.
+    fn window_size(&self) -> usize
+    {
+        self.window.min(self.max_offset)
+    }
+}
+
+pub fn compress(input: &[u8], profile: &Profile) -> Result<Vec<u8>>
+{
+    compress_progress(input, &[], profile, None)
+}
+
+// Compress with a dictionary as prefix. Decoder contract: the dictionary must sit immediately
+// before the output buffer so that matches can reach back into it.
+#[cfg(test)]
+pub fn compress_dict(input: &[u8], dict: &[u8], profile: &Profile) -> Result<Vec<u8>>
+{
+    compress_progress(input, dict, profile, None)
+}
+
+// Compress with a dictionary as prefix, reporting apultra's progress as the number of input bytes
+// processed & the compressed size so far.
+pub fn compress_progress(
+    input: &[u8],
+    dict: &[u8],
+    profile: &Profile,
+    progress: Option<&dyn Fn(i64, i64)>,
+) -> Result<Vec<u8>>
+{
+    let window_size = profile.window_size();
+    let dictionary_size = dict.len();
+    let flags = profile.flags;
     let stats = None;
-    let mut buf = apultra::compress(input, window_size, dictionary_size, flags, progress, stats)?;
+    // apultra skips the leading dictionary bytes. Only copy the input when there is one.
+    let input = match dict.is_empty()
+    {
+        | true => Cow::Borrowed(input),
+        | false => Cow::Owned([dict, input].concat()),
+    };
+    let mut buf = apultra::compress(&input, window_size, dictionary_size, flags, progress, stats)?;
     let prefix = vec![0xFF, 0xFF]; // Add 0xFFFF prefix.
     buf.splice(0..0, prefix);
     buf.resize(buf.len(), 0);
@@ -17,6 +97,13 @@ pub fn compress(input: &[u8]) -> Result<Vec<u8>>
 }
 
 pub fn decompress(input: &[u8]) -> Result<Vec<u8>>
+{
+    decompress_apultra(input, &[])
+}
+
+// Decompress 0xFFFF prefixed data with apultra given the dictionary it was compressed with, which
+// apultra takes as a prefix of the stream as when compressing.
+fn decompress_apultra(input: &[u8], dict: &[u8]) -> Result<Vec<u8>>
 {
     if input.len() < 2
     {
@@ -30,11 +117,73 @@ pub fn decompress(input: &[u8]) -> Result<Vec<u8>>
     {
         return Err(AplibDecompressInvalidheaderError());
     }
-    let dictionary_size = 0;
+    let dictionary_size = dict.len();
     let flags = 0;
 
     let buf = unsafe { input.get_unchecked(2..) };
-    Ok(apultra::decompress(buf, dictionary_size, flags)?)
+    let buf = match dict.is_empty()
+    {
+        | true => Cow::Borrowed(buf),
+        | false => Cow::Owned([dict, buf].concat()),
+    };
+    Ok(apultra::decompress(&buf, dictionary_size, flags)?)
+}
+
+// Decompress 0xFFFF prefixed data given the dictionary it was compressed with. Uses the
+// pure-Rust decoder rather than apultra.
+pub fn decompress_dict(input: &[u8], dict: &[u8]) -> Result<Vec<u8>>
+{
+    if input.len() < 2
+    {
+        return Err(AplibDecompressShortHeaderError());
+    }
+    if input[..2] != [0xFF, 0xFF]
+    {
+        return Err(AplibDecompressInvalidheaderError());
+    }
+    depack::depack(&input[2..], dict, depack::MAX_SIZE)
+}
+
+// Decompress 0xFFFF prefixed data given its dictionary, along with its compressed size including
+// the prefix.
+pub fn decompress_len(input: &[u8], dict: &[u8]) -> Result<(Vec<u8>, usize)>
+{
+    if input.len() < 2
+    {
+        return Err(AplibDecompressShortHeaderError());
+    }
+    if input[..2] != [0xFF, 0xFF]
+    {
+        return Err(AplibDecompressInvalidheaderError());
+    }
+    let (data, tokens) = depack::depack_tokens(&input[2..], dict, depack::MAX_SIZE)?;
+    Ok((data, tokens.bytes + 2))
+}
+
+// Check that both the pure-Rust decoder and apultra decode a stream back to the original data.
+pub fn verify(input: &[u8], dict: &[u8], original: &[u8]) -> Result<()>
+{
+    if decompress_dict(input, dict)? != original
+    {
+        return Err(AplibVerifyError("pure-Rust"));
+    }
+    if decompress_apultra(input, dict)? != original
+    {
+        return Err(AplibVerifyError("apultra"));
+    }
+    Ok(())
+}
+
+// Build a shared dictionary from the start of the given entries, in order, up to a size.
+pub fn dictionary<'a, I: IntoIterator<Item = &'a [u8]>>(entries: I, size: usize) -> Vec<u8>
+{
+    let mut dict = Vec::new();
+    for entry in entries
+    {
+        let len = entry.len().min(size - dict.len());
+        dict.extend_from_slice(&entry[..len]);
+    }
+    dict
 }
 
 #[cfg(test)]
@@ -42,16 +191,25 @@ mod tests
 {
     use std::intrinsics::transmute;
 
-    use super::{AplibDecompressInvalidheaderError, AplibDecompressShortHeaderError};
+    use super::{AplibDecompressInvalidheaderError, AplibDecompressShortHeaderError, Profile};
     #[test]
     fn compress()
     {
         let input_data = vec![0; 100];
-        let compressed = super::compress(&input_data).unwrap();
+        let compressed = super::compress(&input_data, &Profile::default()).unwrap();
         assert_eq!(compressed.len(), 8);
         assert_eq!(compressed, [0xFF, 0xFF, 0, 173, 1, 86, 192, 0]);
     }
 
+    #[test]
+    fn profile_builtin()
+    {
+        assert_eq!(Profile::builtin("default"), Some(Profile::default()));
+        assert_eq!(Profile::builtin("fast").unwrap().window_size(), 0x7CFF);
+        assert_eq!(Profile::builtin("near").unwrap().window_size(), 0x4FF);
+        assert_eq!(Profile::builtin("other"), None);
+    }
+
     #[test]
     fn decompress()
     {
@@ -61,11 +219,69 @@ mod tests
         assert_eq!(decompressed, [0; 100]);
     }
 
+    #[test]
+    fn decompress_dict()
+    {
+        let input_data = vec![0xFF, 0xFF, 0, 173, 1, 86, 192, 0];
+        assert_eq!(super::decompress_dict(&input_data, &[]).unwrap(), [0; 100]);
+
+        // Literal followed by a short match reaching back into the dictionary.
+        let input_data = vec![0xFF, 0xFF, 0x01, 0xD8, 0x09, 0x00];
+        assert_eq!(super::decompress_dict(&input_data, &[1, 2, 3, 4]).unwrap(), [1, 2, 3, 4]);
+
+        let err = super::decompress_dict(&input_data, &[]).unwrap_err();
+        assert_eq!(err.to_string(), "Aplib Decompression Error: Corrupt stream at byte 3");
+    }
+
+    #[test]
+    fn decompress_len()
+    {
+        let mut input_data = vec![0xFF, 0xFF, 0, 173, 1, 86, 192, 0];
+        input_data.extend([0x11; 4]); // trailing data.
+        let (data, size) = super::decompress_len(&input_data, &[]).unwrap();
+        assert_eq!(data, [0; 100]);
+        assert_eq!(size, 8);
+    }
+
+    #[test]
+    fn compress_dict()
+    {
+        let dict = [1, 2, 3, 4, 5, 6, 7, 8];
+        let input_data = [1, 2, 3, 4, 5, 6, 7, 8, 9];
+        let compressed = super::compress_dict(&input_data, &dict, &Profile::default()).unwrap();
+        assert_eq!(super::decompress_dict(&compressed, &dict).unwrap(), input_data);
+    }
+
+    #[test]
+    fn verify()
+    {
+        let input_data = vec![0xFF, 0xFF, 0, 173, 1, 86, 192, 0];
+        super::verify(&input_data, &[], &[0; 100]).unwrap();
+        let err = super::verify(&input_data, &[], &[1; 100]).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Aplib Verification Error: pure-Rust decoder output differs from the original data"
+        );
+
+        // Dictionary streams are checked by both decoders too.
+        let input_data = vec![0xFF, 0xFF, 0x01, 0xD8, 0x09, 0x00];
+        super::verify(&input_data, &[1, 2, 3, 4], &[1, 2, 3, 4]).unwrap();
+        assert_eq!(super::decompress_apultra(&input_data, &[1, 2, 3, 4]).unwrap(), [1, 2, 3, 4]);
+    }
+
+    #[test]
+    fn dictionary()
+    {
+        let entries: [&[u8]; 3] = [&[1, 2], &[3, 4, 5], &[6]];
+        assert_eq!(super::dictionary(entries, 4), [1, 2, 3, 4]);
+        assert_eq!(super::dictionary(entries, 8), [1, 2, 3, 4, 5, 6]);
+    }
+
     #[test]
     fn compress_size_zero_error()
     {
         let input_data = vec![];
-        let err = super::compress(&input_data).unwrap_err();
+        let err = super::compress(&input_data, &Profile::default()).unwrap_err();
         assert_eq!(err.to_string(), "Aplib Compression Error: Input size of zero");
     }
 
@@ -74,7 +290,7 @@ mod tests
     {
         let raw = [0xFF, 0xFF, 0xFF, 0xFF];
         let data: &[u8] = unsafe { transmute(raw) };
-        let err = super::compress(&data).unwrap_err();
+        let err = super::compress(&data, &Profile::default()).unwrap_err();
         assert_eq!(
             err.to_string(),
             "Aplib Compression Error: memory allocation failed because the memory allocator returned an error"
@@ -0,0 +1,120 @@
+use crate::{
+    error::{
+        Error::{BlobChildRangeError, BlobChildSizeError},
+        JsonError,
+    },
+    json::SubAssembly,
+    result::Result,
+};
+
+// Decompressed single-blob entry (e.g. 'cinematicProgram') with views of its named children.
+#[derive(Debug)]
+pub struct Blob
+{
+    pub field:    String,
+    pub data:     Vec<u8>,
+    pub children: Vec<SubAssembly>,
+}
+
+impl Blob
+{
+    pub fn new(field: String, data: Vec<u8>, children: Vec<SubAssembly>) -> Result<Blob>
+    {
+        // Check that every child lies within the decompressed data.
+        if let Some(child) = children.iter().find(|c| c.range.end > data.len())
+        {
+            let path = format!("/assembly/{}/assembly/{}", field, child.key);
+            return Err(BlobChildRangeError(path, child.range.end, data.len()));
+        }
+        Ok(Blob { field, data, children })
+    }
+
+    fn find(&self, key: &str) -> Result<&SubAssembly>
+    {
+        let path = format!("/assembly/{}/assembly/{}", self.field, key);
+        self.children.iter().find(|c| c.key == key).ok_or(JsonError(path))
+    }
+
+    pub fn child<S: AsRef<str>>(&self, key: S) -> Result<&[u8]>
+    {
+        let range = self.find(key.as_ref())?.range.clone();
+        Ok(&self.data[range])
+    }
+
+    pub fn replace<S: AsRef<str>>(&mut self, key: S, bytes: &[u8]) -> Result<()>
+    {
+        let key = key.as_ref();
+        let range = self.find(key)?.range.clone();
+
+        // Children are addressed by fixed offsets, so the size must not change.
+        if bytes.len() != range.len()
+        {
+            return Err(BlobChildSizeError(key.to_string(), bytes.len(), range.len()));
+        }
+        self.data[range].copy_from_slice(bytes);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::Blob;
+    use crate::json::SubAssembly;
+
+    fn blob() -> Blob
+    {
+        let children = vec![
+            SubAssembly { key: "a".into(), name: "A".into(), range: 0..2 },
+            SubAssembly { key: "b".into(), name: "B".into(), range: 2..5 },
+        ];
+        Blob::new("test".into(), vec![0, 1, 2, 3, 4, 5], children).unwrap()
+    }
+
+    #[test]
+    fn child()
+    {
+        let blob = blob();
+        assert_eq!(blob.child("a").unwrap(), [0, 1]);
+        assert_eq!(blob.child("b").unwrap(), [2, 3, 4]);
+    }
+
+    #[test]
+    fn replace()
+    {
+        let mut blob = blob();
+        blob.replace("b", &[9, 9, 9]).unwrap();
+        assert_eq!(blob.data, [0, 1, 9, 9, 9, 5]);
+    }
+
+    #[test]
+    fn child_error()
+    {
+        let err = blob().child("c").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Error Parsing: failed to find JSON entry `/assembly/test/assembly/c`"
+        );
+    }
+
+    #[test]
+    fn replace_size_error()
+    {
+        let err = blob().replace("a", &[9]).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Blob Error: `a` replacement size differs from original (1!=2)"
+        );
+    }
+
+    #[test]
+    fn range_error()
+    {
+        let children = vec![SubAssembly { key: "a".into(), name: "A".into(), range: 0..8 }];
+        let err = Blob::new("test".into(), vec![0; 4], children).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Blob Error: `/assembly/test/assembly/a` range exceeds decompressed data size (8>4)"
+        );
+    }
+}
@@ -0,0 +1,193 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+use crate::{
+    aplib::{self, Profile},
+    result::Result,
+    workspace::hash,
+};
+
+// Default size limit of the cache directory (64 MiB).
+pub const LIMIT: u64 = 64 << 20;
+
+// On-disk cache of compressed streams keyed by their decompressed content, dictionary, codec &
+// profile settings. Least recently used streams are evicted once the directory exceeds its limit.
+#[derive(Clone, Debug)]
+pub struct Cache
+{
+    dir:   PathBuf,
+    limit: u64, // Size limit of the cached streams in bytes.
+}
+
+impl Cache
+{
+    pub fn new<P: AsRef<Path>>(dir: P, limit: u64) -> Result<Cache>
+    {
+        fs::create_dir_all(&dir)?;
+        Ok(Cache { dir: dir.as_ref().to_path_buf(), limit })
+    }
+
+    // Name of the cache file of a stream. Profile names are left out, only their settings matter.
+    fn file(input: &[u8], dict: &[u8], profile: &Profile) -> String
+    {
+        let settings = format!("{}:{}:{}", profile.window, profile.max_offset, profile.flags);
+        #[rustfmt::skip]
+        let name = format!("{:016x}-{:016x}-{}-{:016x}.bin", hash(input), hash(dict), aplib::FORMAT, hash(settings.as_bytes()));
+        name
+    }
+
+    // Look up a cached stream & mark it as recently used. Streams that no longer decode to the
+    // input, e.g. truncated files, are treated as missing.
+    fn get(&self, input: &[u8], dict: &[u8], profile: &Profile) -> Option<Vec<u8>>
+    {
+        let path = self.dir.join(Cache::file(input, dict, profile));
+        let data = fs::read(&path).ok()?;
+        if aplib::decompress_dict(&data, dict).ok()? != input
+        {
+            return None;
+        }
+        let file = fs::File::options().write(true).open(&path).ok();
+        file.map(|f| f.set_modified(SystemTime::now()));
+        Some(data)
+    }
+
+    // Store a stream. It's written to a temporary file first so concurrent runs never read a
+    // partial stream.
+    fn insert(&self, input: &[u8], dict: &[u8], profile: &Profile, data: &[u8]) -> Result<()>
+    {
+        let path = self.dir.join(Cache::file(input, dict, profile));
+        let tmp = tempfile::NamedTempFile::new_in(&self.dir)?;
+        fs::write(tmp.path(), data)?;
+        tmp.persist(path).map_err(|e| e.error)?;
+        Ok(())
+    }
+
+    // Compress with a dictionary as prefix, skipping apultra when the stream is cached.
+    pub fn compress_dict(
+        &self,
+        input: &[u8],
+        dict: &[u8],
+        profile: &Profile,
+        progress: Option<&dyn Fn(i64, i64)>,
+    ) -> Result<Vec<u8>>
+    {
+        if let Some(data) = self.get(input, dict, profile)
+        {
+            return Ok(data);
+        }
+        let data = aplib::compress_progress(input, dict, profile, progress)?;
+        self.insert(input, dict, profile, &data)?;
+        Ok(data)
+    }
+
+    // Remove the least recently used streams until the cache fits within its limit. Returns the
+    // number of streams removed.
+    pub fn evict(&self) -> Result<usize>
+    {
+        let mut files = Vec::new();
+        for entry in fs::read_dir(&self.dir)?
+        {
+            let entry = entry?;
+            let meta = entry.metadata()?;
+            if meta.is_file() && entry.path().extension().is_some_and(|e| e == "bin")
+            {
+                files.push((meta.modified()?, meta.len(), entry.path()));
+            }
+        }
+        files.sort();
+
+        let mut size: u64 = files.iter().map(|(_, len, _)| len).sum();
+        let mut evicted = 0;
+        for (_, len, path) in files.iter()
+        {
+            if size <= self.limit
+            {
+                break;
+            }
+            fs::remove_file(path)?;
+            size -= len;
+            evicted += 1;
+        }
+        Ok(evicted)
+    }
+}
+
+// Compress with a dictionary as prefix through the cache, if there is one.
+pub fn compress_dict(
+    cache: Option<&Cache>,
+    input: &[u8],
+    dict: &[u8],
+    profile: &Profile,
+    progress: Option<&dyn Fn(i64, i64)>,
+) -> Result<Vec<u8>>
+{
+    match cache
+    {
+        | Some(cache) => cache.compress_dict(input, dict, profile, progress),
+        | None => aplib::compress_progress(input, dict, profile, progress),
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use std::{
+        fs,
+        time::{Duration, SystemTime},
+    };
+
+    use super::Cache;
+    use crate::aplib::{self, Profile};
+
+    #[test]
+    fn compress_dict()
+    {
+        let dir = tempfile::tempdir().expect("Failed to create temp directory.");
+        let cache = Cache::new(dir.path(), super::LIMIT).unwrap();
+        let profile = Profile::default();
+        let data = cache.compress_dict(&[0x11; 10], &[], &profile, None).unwrap();
+        assert_eq!(data, aplib::compress(&[0x11; 10], &profile).unwrap());
+        let file = dir.path().join(Cache::file(&[0x11; 10], &[], &profile));
+        assert_eq!(fs::read(&file).unwrap(), data);
+
+        // Hits are served from disk, while other settings & dictionaries miss.
+        fs::write(&file, [0xFF, 0xFF, 0x11, 0x11, 0x00, 0x00]).unwrap();
+        assert_eq!(cache.get(&[0x11; 10], &[], &profile), None);
+        assert_eq!(cache.compress_dict(&[0x11; 10], &[], &profile, None).unwrap(), data);
+        assert_eq!(cache.get(&[0x11; 10], &[], &profile), Some(data));
+        let fast = Profile::builtin("fast").unwrap();
+        assert_eq!(cache.get(&[0x11; 10], &[], &fast), None);
+        assert_eq!(cache.get(&[0x11; 10], &[0x11], &profile), None);
+
+        // Profiles with the same settings share streams.
+        let renamed = Profile { name: "renamed".into(), ..Profile::default() };
+        assert_eq!(
+            Cache::file(&[0x11; 10], &[], &renamed),
+            Cache::file(&[0x11; 10], &[], &profile)
+        );
+    }
+
+    #[test]
+    fn evict()
+    {
+        let dir = tempfile::tempdir().expect("Failed to create temp directory.");
+        let cache = Cache::new(dir.path(), 8).unwrap();
+        let now = SystemTime::now();
+        for (i, name) in ["a.bin", "b.bin", "c.bin"].iter().enumerate()
+        {
+            let path = dir.path().join(name);
+            fs::write(&path, [0; 4]).unwrap();
+            let file = fs::File::options().write(true).open(&path).unwrap();
+            file.set_modified(now - Duration::from_secs(10 - i as u64)).unwrap();
+        }
+
+        // The oldest stream goes first.
+        assert_eq!(cache.evict().unwrap(), 1);
+        assert!(!dir.path().join("a.bin").exists());
+        assert!(dir.path().join("b.bin").exists());
+        assert_eq!(cache.evict().unwrap(), 0);
+    }
+}
@@ -0,0 +1,174 @@
+use std::fmt;
+
+use crate::{
+    aplib::{self, Profile},
+    cache::{self, Cache},
+    lzss,
+    result::Result,
+};
+
+// Definition format identifier for data stored uncompressed.
+pub const RAW: &str = "raw";
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Codec
+{
+    Lzss,
+    Aplib,
+    Raw, // Only used by entries whose code was changed to copy the data as is.
+}
+
+// Codec & settings an entry may be compressed with, e.g. 'ff6-aplib:fast' or 'ff6-lzss'.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Candidate
+{
+    Aplib(Profile),
+    Lzss, // Optimally parsed.
+    Raw,
+}
+
+impl Codec
+{
+    // Detect the codec of compressed data by its header. aPLib data is prefixed with 0xFFFF. Raw
+    // data has no header, so it's never detected.
+    pub fn detect(input: &[u8]) -> Codec
+    {
+        match input.get(0..2)
+        {
+            | Some([0xFF, 0xFF]) => Codec::Aplib,
+            | _ => Codec::Lzss,
+        }
+    }
+
+    // Get the codec of a definition format, e.g. 'ff6-lzss'.
+    pub fn from_format(format: &str) -> Option<Codec>
+    {
+        [Codec::Lzss, Codec::Aplib, Codec::Raw].iter().copied().find(|c| c.format() == format)
+    }
+
+    pub fn format(self) -> &'static str
+    {
+        match self
+        {
+            | Codec::Lzss => lzss::FORMAT,
+            | Codec::Aplib => aplib::FORMAT,
+            | Codec::Raw => RAW,
+        }
+    }
+
+    pub fn decompress(self, input: &[u8]) -> Result<Vec<u8>>
+    {
+        match self
+        {
+            | Codec::Lzss => Ok(lzss::decompress(input)?.0),
+            | Codec::Aplib => aplib::decompress(input),
+            | Codec::Raw => Ok(input.to_vec()),
+        }
+    }
+
+    // Decompress data along with its compressed size. Only aPLib data is compressed against a
+    // dictionary.
+    pub fn decompress_len(self, input: &[u8], dict: &[u8]) -> Result<(Vec<u8>, usize)>
+    {
+        match self
+        {
+            | Codec::Lzss => lzss::decompress(input),
+            | Codec::Aplib => aplib::decompress_len(input, dict),
+            | Codec::Raw => Ok((input.to_vec(), input.len())),
+        }
+    }
+}
+
+impl Candidate
+{
+    pub fn codec(&self) -> Codec
+    {
+        match self
+        {
+            | Candidate::Aplib(_) => Codec::Aplib,
+            | Candidate::Lzss => Codec::Lzss,
+            | Candidate::Raw => Codec::Raw,
+        }
+    }
+
+    // Compress data, with a dictionary as prefix for aPLib. The other codecs can't use one.
+    pub fn compress(
+        &self,
+        input: &[u8],
+        dict: &[u8],
+        cache: Option<&Cache>,
+        progress: Option<&dyn Fn(i64, i64)>,
+    ) -> Result<Vec<u8>>
+    {
+        match self
+        {
+            | Candidate::Aplib(profile) =>
+            {
+                cache::compress_dict(cache, input, dict, profile, progress)
+            },
+            | Candidate::Lzss => lzss::compress(input),
+            | Candidate::Raw => Ok(input.to_vec()),
+        }
+    }
+}
+
+impl fmt::Display for Candidate
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+    {
+        match self
+        {
+            | Candidate::Aplib(profile) => write!(f, "{}:{}", aplib::FORMAT, profile.name),
+            | candidate => write!(f, "{}", candidate.codec().format()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::{Candidate, Codec};
+    use crate::aplib::Profile;
+
+    #[test]
+    fn detect()
+    {
+        assert_eq!(Codec::detect(&[0xFF, 0xFF, 0x00]), Codec::Aplib);
+        assert_eq!(Codec::detect(&[0x06, 0x00, 0x01]), Codec::Lzss);
+        assert_eq!(Codec::detect(&[0xFF]), Codec::Lzss);
+    }
+
+    #[test]
+    fn decompress_lzss()
+    {
+        let input = [0x06, 0x00, 0x01, 0x11, 0xDE, 0x37];
+        let data = Codec::detect(&input).decompress(&input).unwrap();
+        assert_eq!(data, [0x11; 10]);
+    }
+
+    #[test]
+    fn decompress_len()
+    {
+        let input = [0x06, 0x00, 0x01, 0x11, 0xDE, 0x37, 0x22];
+        let (data, size) = Codec::detect(&input).decompress_len(&input, &[]).unwrap();
+        assert_eq!(data, [0x11; 10]);
+        assert_eq!(size, 6);
+
+        let input = [0xFF, 0xFF, 0, 173, 1, 86, 192, 0, 0x22];
+        let (data, size) = Codec::detect(&input).decompress_len(&input, &[]).unwrap();
+        assert_eq!(data, [0; 100]);
+        assert_eq!(size, 8);
+    }
+
+    #[test]
+    fn candidate()
+    {
+        let aplib = Candidate::Aplib(Profile::builtin("fast").unwrap());
+        assert_eq!(aplib.to_string(), "ff6-aplib:fast");
+        assert_eq!(Candidate::Lzss.to_string(), "ff6-lzss");
+        let data = Candidate::Lzss.compress(&[0x11; 10], &[], None, None).unwrap();
+        assert_eq!(data, [0x06, 0x00, 0x01, 0x11, 0xDE, 0x37]);
+        let data = Candidate::Raw.compress(&[0x11; 10], &[0x22], None, None).unwrap();
+        assert_eq!(Codec::Raw.decompress(&data).unwrap(), [0x11; 10]);
+    }
+}
@@ -0,0 +1,60 @@
+use crate::{codec::Codec, depack, lzss, result::Result};
+
+// Rough cycle costs of the injected aPLib decoder per token. Use the CPU harness to measure the
+// real decoder when these need refining.
+const LITERAL: u64 = 30;
+const MATCH: u64 = 60;
+const MATCH_BYTE: u64 = 8;
+const BIT: u64 = 12;
+
+// Rough cycle cost of the game's LZSS decoder per byte, which passes every byte through its ring
+// buffer.
+const LZSS_BYTE: u64 = 40;
+
+// Rough cycle cost per byte of copying raw data with a block move.
+const RAW_BYTE: u64 = 7;
+
+// Modelled decode cycles of data of a given codec.
+pub fn codec_cycles(codec: Codec, input: &[u8], dict: &[u8]) -> Result<u64>
+{
+    match codec
+    {
+        | Codec::Aplib =>
+        {
+            let (_, t) = depack::depack_tokens(&input[2..], dict, depack::MAX_SIZE)?;
+            let tokens = [(t.literals, LITERAL), (t.matches, MATCH), (t.match_bytes, MATCH_BYTE)];
+            let cycles: u64 = tokens.iter().map(|&(n, cost)| n as u64 * cost).sum();
+            Ok(cycles + t.bits as u64 * BIT)
+        },
+        | Codec::Lzss => Ok(lzss::decompress(input)?.0.len() as u64 * LZSS_BYTE),
+        | Codec::Raw => Ok(input.len() as u64 * RAW_BYTE),
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::codec_cycles;
+    use crate::codec::Codec;
+
+    #[test]
+    fn cycles_aplib()
+    {
+        let input_data = vec![0xFF, 0xFF, 0, 173, 1, 86, 192, 0];
+        let cycles = codec_cycles(Codec::Aplib, &input_data, &[]).unwrap();
+        assert_eq!(cycles, 30 + 60 + 99 * 8 + 19 * 12);
+    }
+
+    #[test]
+    fn cycles_lzss()
+    {
+        let input_data = vec![0x06, 0x00, 0x01, 0x11, 0xDE, 0x37];
+        assert_eq!(codec_cycles(Codec::Lzss, &input_data, &[]).unwrap(), 10 * 40);
+    }
+
+    #[test]
+    fn cycles_raw()
+    {
+        assert_eq!(codec_cycles(Codec::Raw, &[0xFF; 10], &[]).unwrap(), 10 * 7);
+    }
+}
@@ -0,0 +1,976 @@
+use crate::{
+    error::Error::{CpuDecimalError, CpuLimitError, CpuOpcodeError},
+    result::Result,
+};
+
+// Status flags.
+const C: u8 = 0x01;
+const Z: u8 = 0x02;
+const I: u8 = 0x04;
+const D: u8 = 0x08;
+const X: u8 = 0x10;
+const M: u8 = 0x20;
+const V: u8 = 0x40;
+const N: u8 = 0x80;
+
+// Stack pointer the harness starts from.
+const STACK: u16 = 0x1FFF;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+enum Mode
+{
+    Imm,
+    Dp,
+    DpX,
+    DpY,
+    DpInd,
+    DpIndLong,
+    DpXInd,
+    DpIndY,
+    DpIndLongY,
+    Abs,
+    AbsX,
+    AbsY,
+    Long,
+    LongX,
+    Sr,
+    SrIndY,
+}
+
+impl Mode
+{
+    // Approximate cycles of an 8-bit read through the addressing mode.
+    fn cycles(self) -> u64
+    {
+        match self
+        {
+            | Mode::Imm => 2,
+            | Mode::Dp => 3,
+            | Mode::DpX | Mode::DpY | Mode::Abs | Mode::AbsX | Mode::AbsY | Mode::Sr => 4,
+            | Mode::DpInd | Mode::DpIndY | Mode::Long | Mode::LongX => 5,
+            | Mode::DpIndLong | Mode::DpXInd | Mode::DpIndLongY => 6,
+            | Mode::SrIndY => 7,
+        }
+    }
+}
+
+// Addressing mode of the 'ORA, AND, EOR, ADC, STA, LDA, CMP, SBC' group by the low opcode bits.
+fn group_mode(op: u8) -> Option<Mode>
+{
+    match op & 0x1F
+    {
+        | 0x01 => Some(Mode::DpXInd),
+        | 0x03 => Some(Mode::Sr),
+        | 0x05 => Some(Mode::Dp),
+        | 0x07 => Some(Mode::DpIndLong),
+        | 0x09 if op != 0x89 => Some(Mode::Imm), // 0x89 is 'BIT #'.
+        | 0x0D => Some(Mode::Abs),
+        | 0x0F => Some(Mode::Long),
+        | 0x11 => Some(Mode::DpIndY),
+        | 0x12 => Some(Mode::DpInd),
+        | 0x13 => Some(Mode::SrIndY),
+        | 0x15 => Some(Mode::DpX),
+        | 0x17 => Some(Mode::DpIndLongY),
+        | 0x19 => Some(Mode::AbsY),
+        | 0x1D => Some(Mode::AbsX),
+        | 0x1F => Some(Mode::LongX),
+        | _ => None,
+    }
+}
+
+// Minimal 65816 interpreter with a flat HiROM memory map of ROM and WRAM, for running the game's
+// decompression routines. Cycle counts are approximate CPU cycles and ignore memory speed.
+pub struct Cpu<'a>
+{
+    rom:        &'a [u8],
+    wram:       Vec<u8>,
+    wm_addr:    usize,      // WRAM port address ($2181-$2183).
+    io:         [u8; 0x20], // CPU registers ($4200-$421F).
+    a:          u16,
+    x:          u16,
+    y:          u16,
+    s:          u16,
+    d:          u16,
+    db:         u8,
+    pb:         u8,
+    pc:         u16,
+    p:          u8,
+    e:          bool,
+    pub cycles: u64,
+}
+
+impl<'a> Cpu<'a>
+{
+    pub fn new(rom: &'a [u8]) -> Cpu<'a>
+    {
+        #[rustfmt::skip]
+        let cpu = Cpu {
+            rom, wram: vec![0; 0x20000], wm_addr: 0, io: [0; 0x20],
+            a: 0, x: 0, y: 0, s: STACK, d: 0, db: 0, pb: 0, pc: 0, p: M | X, e: false, cycles: 0,
+        };
+        cpu
+    }
+
+    pub fn set_flags(&mut self, p: u8)
+    {
+        self.p = p;
+        self.fix_flags();
+    }
+
+    pub fn read(&mut self, addr: usize) -> u8
+    {
+        let (bank, off) = (addr >> 16 & 0xFF, addr & 0xFFFF);
+        match (bank, off)
+        {
+            | (0x7E..=0x7F, _) => self.wram[addr & 0x1FFFF],
+            | (0x40..=0x7D | 0xC0..=0xFF, _) | (_, 0x8000..=0xFFFF) => self.rom_at(addr),
+            | (_, 0x0000..=0x1FFF) => self.wram[off],
+            | (_, 0x2180) =>
+            {
+                let b = self.wram[self.wm_addr];
+                self.wm_addr = (self.wm_addr + 1) & 0x1FFFF;
+                b
+            },
+            | (_, 0x4200..=0x421F) => self.io[off - 0x4200],
+            | _ => 0, // open bus.
+        }
+    }
+
+    pub fn write(&mut self, addr: usize, value: u8)
+    {
+        let (bank, off) = (addr >> 16 & 0xFF, addr & 0xFFFF);
+        match (bank, off)
+        {
+            | (0x7E..=0x7F, _) => self.wram[addr & 0x1FFFF] = value,
+            | (0x40..=0x7D | 0xC0..=0xFF, _) | (_, 0x8000..=0xFFFF) => (), // ROM.
+            | (_, 0x0000..=0x1FFF) => self.wram[off] = value,
+            | (_, 0x2180) =>
+            {
+                self.wram[self.wm_addr] = value;
+                self.wm_addr = (self.wm_addr + 1) & 0x1FFFF;
+            },
+            | (_, 0x2181) => self.wm_addr = self.wm_addr & 0x1FF00 | value as usize,
+            | (_, 0x2182) => self.wm_addr = self.wm_addr & 0x100FF | (value as usize) << 8,
+            | (_, 0x2183) => self.wm_addr = self.wm_addr & 0x0FFFF | (value as usize & 1) << 16,
+            | (_, 0x4200..=0x421F) =>
+            {
+                self.io[off - 0x4200] = value;
+                self.math(off);
+            },
+            | _ => (),
+        }
+    }
+
+    fn rom_at(&self, addr: usize) -> u8
+    {
+        self.rom.get(addr & 0x3FFFFF).copied().unwrap_or(0)
+    }
+
+    // Perform hardware multiplication and division once their last operand is written.
+    fn math(&mut self, off: usize)
+    {
+        let io = &mut self.io;
+        match off
+        {
+            | 0x4203 =>
+            {
+                let r = io[0x02] as u16 * io[0x03] as u16;
+                io[0x16..0x18].copy_from_slice(&r.to_le_bytes());
+            },
+            | 0x4206 =>
+            {
+                let n = u16::from_le_bytes([io[0x04], io[0x05]]);
+                let (q, r) = match io[0x06] as u16
+                {
+                    | 0 => (0xFFFF, n),
+                    | d => (n / d, n % d),
+                };
+                io[0x14..0x16].copy_from_slice(&q.to_le_bytes());
+                io[0x16..0x18].copy_from_slice(&r.to_le_bytes());
+            },
+            | _ => (),
+        }
+    }
+
+    pub fn read16(&mut self, addr: usize) -> u16
+    {
+        u16::from_le_bytes([self.read(addr), self.read((addr + 1) & 0xFFFFFF)])
+    }
+
+    fn read24(&mut self, addr: usize) -> usize
+    {
+        self.read16(addr) as usize | (self.read((addr + 2) & 0xFFFFFF) as usize) << 16
+    }
+
+    pub fn write24(&mut self, addr: usize, value: usize)
+    {
+        for i in 0..3
+        {
+            self.write((addr + i) & 0xFFFFFF, (value >> (8 * i)) as u8);
+        }
+    }
+
+    // Program counter as a 24-bit address.
+    fn pc_addr(&self) -> usize
+    {
+        (self.pb as usize) << 16 | self.pc as usize
+    }
+
+    fn fetch(&mut self) -> u8
+    {
+        let b = self.read(self.pc_addr());
+        self.pc = self.pc.wrapping_add(1);
+        b
+    }
+
+    fn fetch16(&mut self) -> u16
+    {
+        u16::from_le_bytes([self.fetch(), self.fetch()])
+    }
+
+    fn fetch24(&mut self) -> usize
+    {
+        self.fetch16() as usize | (self.fetch() as usize) << 16
+    }
+
+    fn push(&mut self, value: u8)
+    {
+        self.write(self.s as usize, value);
+        self.s = self.s.wrapping_sub(1);
+        if self.e
+        {
+            self.s = 0x100 | self.s & 0xFF;
+        }
+    }
+
+    fn pull(&mut self) -> u8
+    {
+        self.s = self.s.wrapping_add(1);
+        if self.e
+        {
+            self.s = 0x100 | self.s & 0xFF;
+        }
+        self.read(self.s as usize)
+    }
+
+    fn push16(&mut self, value: u16)
+    {
+        self.push((value >> 8) as u8);
+        self.push(value as u8);
+    }
+
+    fn pull16(&mut self) -> u16
+    {
+        u16::from_le_bytes([self.pull(), self.pull()])
+    }
+
+    // Whether the accumulator or the index registers are 8-bit.
+    fn m8(&self) -> bool
+    {
+        self.e || self.p & M != 0
+    }
+
+    fn x8(&self) -> bool
+    {
+        self.e || self.p & X != 0
+    }
+
+    fn flag(&mut self, flag: u8, set: bool)
+    {
+        match set
+        {
+            | true => self.p |= flag,
+            | false => self.p &= !flag,
+        }
+    }
+
+    // Force flags & registers into a consistent state after the flags or mode changed.
+    fn fix_flags(&mut self)
+    {
+        if self.e
+        {
+            self.p |= M | X;
+            self.s = 0x100 | self.s & 0xFF;
+        }
+        if self.p & X != 0
+        {
+            self.x &= 0xFF;
+            self.y &= 0xFF;
+        }
+    }
+
+    fn set_nz(&mut self, value: u16, wide: bool)
+    {
+        let (value, top) = if wide { (value, 0x8000) } else { (value & 0xFF, 0x80) };
+        self.flag(Z, value == 0);
+        self.flag(N, value & top != 0);
+    }
+
+    fn acc(&self) -> u16
+    {
+        if self.m8()
+        {
+            self.a & 0xFF
+        }
+        else
+        {
+            self.a
+        }
+    }
+
+    fn set_acc(&mut self, value: u16)
+    {
+        self.a = if self.m8() { self.a & 0xFF00 | value & 0xFF } else { value };
+        self.set_nz(value, !self.m8());
+    }
+
+    // Mask a value to the width of the index registers and set flags.
+    fn index(&mut self, value: u16) -> u16
+    {
+        let value = if self.x8() { value & 0xFF } else { value };
+        self.set_nz(value, !self.x8());
+        value
+    }
+
+    // Resolve the effective address of a memory operand.
+    fn addr(&mut self, mode: Mode) -> usize
+    {
+        let db = (self.db as usize) << 16;
+        let (x, y) = (self.x, self.y as usize);
+        match mode
+        {
+            | Mode::Imm => unreachable!(),
+            | Mode::Dp => self.dp(0),
+            | Mode::DpX => self.dp(x),
+            | Mode::DpY => self.dp(y as u16),
+            | Mode::DpInd =>
+            {
+                let ptr = self.dp(0);
+                db | self.read16(ptr) as usize
+            },
+            | Mode::DpIndLong =>
+            {
+                let ptr = self.dp(0);
+                self.read24(ptr)
+            },
+            | Mode::DpXInd =>
+            {
+                let ptr = self.dp(x);
+                db | self.read16(ptr) as usize
+            },
+            | Mode::DpIndY =>
+            {
+                let ptr = self.dp(0);
+                ((db | self.read16(ptr) as usize) + y) & 0xFFFFFF
+            },
+            | Mode::DpIndLongY =>
+            {
+                let ptr = self.dp(0);
+                (self.read24(ptr) + y) & 0xFFFFFF
+            },
+            | Mode::Abs => db | self.fetch16() as usize,
+            | Mode::AbsX => ((db | self.fetch16() as usize) + x as usize) & 0xFFFFFF,
+            | Mode::AbsY => ((db | self.fetch16() as usize) + y) & 0xFFFFFF,
+            | Mode::Long => self.fetch24(),
+            | Mode::LongX => (self.fetch24() + x as usize) & 0xFFFFFF,
+            | Mode::Sr => self.s.wrapping_add(self.fetch() as u16) as usize,
+            | Mode::SrIndY =>
+            {
+                let ptr = self.s.wrapping_add(self.fetch() as u16) as usize;
+                ((db | self.read16(ptr) as usize) + y) & 0xFFFFFF
+            },
+        }
+    }
+
+    // Direct page address of the next operand byte plus an index.
+    fn dp(&mut self, index: u16) -> usize
+    {
+        if self.d & 0xFF != 0
+        {
+            self.cycles += 1;
+        }
+        let n = self.fetch() as u16;
+        self.d.wrapping_add(n).wrapping_add(index) as usize
+    }
+
+    // Load an operand of the given width and count the cycles of the access.
+    fn load(&mut self, mode: Mode, wide: bool) -> u16
+    {
+        self.cycles += mode.cycles() + wide as u64;
+        match (mode, wide)
+        {
+            | (Mode::Imm, true) => self.fetch16(),
+            | (Mode::Imm, false) => self.fetch() as u16,
+            | (_, true) =>
+            {
+                let addr = self.addr(mode);
+                self.read16(addr)
+            },
+            | (_, false) =>
+            {
+                let addr = self.addr(mode);
+                self.read(addr) as u16
+            },
+        }
+    }
+
+    fn store(&mut self, mode: Mode, value: u16, wide: bool)
+    {
+        self.cycles += mode.cycles() + wide as u64;
+        let addr = self.addr(mode);
+        self.write(addr, value as u8);
+        if wide
+        {
+            self.write((addr + 1) & 0xFFFFFF, (value >> 8) as u8);
+        }
+    }
+
+    // Read-modify-write the accumulator (no mode) or memory.
+    fn modify(&mut self, mode: Option<Mode>, f: fn(&mut Self, u16, bool) -> u16)
+    {
+        let wide = !self.m8();
+        match mode
+        {
+            | None =>
+            {
+                self.cycles += 2;
+                let value = f(self, self.acc(), wide);
+                self.set_acc(value);
+            },
+            | Some(mode) =>
+            {
+                self.cycles += mode.cycles() + 2 + 2 * wide as u64;
+                let addr = self.addr(mode);
+                let value = match wide
+                {
+                    | true => self.read16(addr),
+                    | false => self.read(addr) as u16,
+                };
+                let value = f(self, value, wide);
+                self.write(addr, value as u8);
+                if wide
+                {
+                    self.write((addr + 1) & 0xFFFFFF, (value >> 8) as u8);
+                }
+            },
+        }
+    }
+
+    fn asl(&mut self, value: u16, wide: bool) -> u16
+    {
+        let top = if wide { 0x8000 } else { 0x80 };
+        self.flag(C, value & top != 0);
+        let value = value << 1;
+        self.set_nz(value, wide);
+        value
+    }
+
+    fn lsr(&mut self, value: u16, wide: bool) -> u16
+    {
+        self.flag(C, value & 1 != 0);
+        let value = value >> 1;
+        self.set_nz(value, wide);
+        value
+    }
+
+    fn rol(&mut self, value: u16, wide: bool) -> u16
+    {
+        let carry = (self.p & C) as u16;
+        let value = self.asl(value, wide) | carry;
+        self.set_nz(value, wide);
+        value
+    }
+
+    fn ror(&mut self, value: u16, wide: bool) -> u16
+    {
+        let top = if wide { 0x8000 } else { 0x80 };
+        let carry = if self.p & C != 0 { top } else { 0 };
+        let value = self.lsr(value, wide) | carry;
+        self.set_nz(value, wide);
+        value
+    }
+
+    fn inc(&mut self, value: u16, wide: bool) -> u16
+    {
+        let value = value.wrapping_add(1);
+        self.set_nz(value, wide);
+        value
+    }
+
+    fn dec(&mut self, value: u16, wide: bool) -> u16
+    {
+        let value = value.wrapping_sub(1);
+        self.set_nz(value, wide);
+        value
+    }
+
+    fn tsb(&mut self, value: u16, _: bool) -> u16
+    {
+        self.flag(Z, value & self.acc() == 0);
+        value | self.acc()
+    }
+
+    fn trb(&mut self, value: u16, _: bool) -> u16
+    {
+        self.flag(Z, value & self.acc() == 0);
+        value & !self.acc()
+    }
+
+    // Binary add with carry. Decimal mode is not supported.
+    fn adc(&mut self, value: u16) -> Result<()>
+    {
+        if self.p & D != 0
+        {
+            return Err(CpuDecimalError(self.pc_addr()));
+        }
+        let (mask, top) = if self.m8() { (0xFF, 0x80) } else { (0xFFFF, 0x8000) };
+        let a = self.acc() as u32;
+        let r = a + value as u32 + (self.p & C) as u32;
+        self.flag(C, r > mask);
+        self.flag(V, !(a ^ value as u32) & (a ^ r) & top != 0);
+        self.set_acc(r as u16);
+        Ok(())
+    }
+
+    fn compare(&mut self, reg: u16, value: u16, wide: bool)
+    {
+        let reg = if wide { reg } else { reg & 0xFF };
+        self.flag(C, reg >= value);
+        self.set_nz(reg.wrapping_sub(value), wide);
+    }
+
+    fn branch(&mut self, cond: bool)
+    {
+        let off = self.fetch() as i8;
+        self.cycles += 2;
+        if cond
+        {
+            self.pc = self.pc.wrapping_add(off as u16);
+            self.cycles += 1;
+        }
+    }
+
+    // Move a block of memory one byte at a time, incrementing (MVN) or decrementing (MVP).
+    fn block_move(&mut self, step: u16)
+    {
+        let dst = self.fetch();
+        let src = self.fetch();
+        self.db = dst;
+        loop
+        {
+            let value = self.read((src as usize) << 16 | self.x as usize);
+            self.write((dst as usize) << 16 | self.y as usize, value);
+            self.x = self.index_step(self.x, step);
+            self.y = self.index_step(self.y, step);
+            self.a = self.a.wrapping_sub(1);
+            self.cycles += 7;
+            if self.a == 0xFFFF
+            {
+                break;
+            }
+        }
+    }
+
+    fn index_step(&self, reg: u16, step: u16) -> u16
+    {
+        let reg = reg.wrapping_add(step);
+        if self.x8()
+        {
+            reg & 0xFF
+        }
+        else
+        {
+            reg
+        }
+    }
+
+    // Call a routine with JSL (long) or JSR and run until it returns, yielding the cycle count.
+    pub fn call(&mut self, routine: usize, long: bool, limit: u64) -> Result<u64>
+    {
+        // Push a return address; the routine is done once it is pulled off the stack again.
+        self.s = STACK;
+        if long
+        {
+            self.push(0);
+        }
+        self.push16(0xFFFF);
+        self.pb = (routine >> 16) as u8;
+        self.pc = routine as u16;
+        self.cycles = 0;
+
+        while self.s != STACK
+        {
+            if self.cycles > limit
+            {
+                return Err(CpuLimitError(limit));
+            }
+            self.step()?;
+        }
+        Ok(self.cycles)
+    }
+
+    pub fn step(&mut self) -> Result<()>
+    {
+        let pc = self.pc_addr();
+        let op = self.fetch();
+        let (m_wide, x_wide) = (!self.m8(), !self.x8());
+
+        if let Some(mode) = group_mode(op)
+        {
+            // ORA, AND, EOR, ADC, STA, LDA, CMP, SBC.
+            if op >> 5 == 4
+            {
+                self.store(mode, self.a, m_wide);
+                return Ok(());
+            }
+            let value = self.load(mode, m_wide);
+            match op >> 5
+            {
+                | 0 => self.set_acc(self.acc() | value),
+                | 1 => self.set_acc(self.acc() & value),
+                | 2 => self.set_acc(self.acc() ^ value),
+                | 3 => self.adc(value)?,
+                | 5 => self.set_acc(value),
+                | 6 => self.compare(self.a, value, m_wide),
+                | _ => self.adc(!value & if m_wide { 0xFFFF } else { 0xFF })?,
+            }
+            return Ok(());
+        }
+        self.execute(op, pc, m_wide, x_wide)
+    }
+
+    // Execute every instruction outside of the 'ORA, AND, ...' group.
+    #[rustfmt::skip]
+    fn execute(&mut self, op: u8, pc: usize, m_wide: bool, x_wide: bool) -> Result<()>
+    {
+        match op
+        {
+            // Read-modify-write.
+            | 0x0A => self.modify(None, Self::asl),
+            | 0x06 => self.modify(Some(Mode::Dp), Self::asl),
+            | 0x0E => self.modify(Some(Mode::Abs), Self::asl),
+            | 0x16 => self.modify(Some(Mode::DpX), Self::asl),
+            | 0x1E => self.modify(Some(Mode::AbsX), Self::asl),
+            | 0x2A => self.modify(None, Self::rol),
+            | 0x26 => self.modify(Some(Mode::Dp), Self::rol),
+            | 0x2E => self.modify(Some(Mode::Abs), Self::rol),
+            | 0x36 => self.modify(Some(Mode::DpX), Self::rol),
+            | 0x3E => self.modify(Some(Mode::AbsX), Self::rol),
+            | 0x4A => self.modify(None, Self::lsr),
+            | 0x46 => self.modify(Some(Mode::Dp), Self::lsr),
+            | 0x4E => self.modify(Some(Mode::Abs), Self::lsr),
+            | 0x56 => self.modify(Some(Mode::DpX), Self::lsr),
+            | 0x5E => self.modify(Some(Mode::AbsX), Self::lsr),
+            | 0x6A => self.modify(None, Self::ror),
+            | 0x66 => self.modify(Some(Mode::Dp), Self::ror),
+            | 0x6E => self.modify(Some(Mode::Abs), Self::ror),
+            | 0x76 => self.modify(Some(Mode::DpX), Self::ror),
+            | 0x7E => self.modify(Some(Mode::AbsX), Self::ror),
+            | 0x1A => self.modify(None, Self::inc),
+            | 0xE6 => self.modify(Some(Mode::Dp), Self::inc),
+            | 0xEE => self.modify(Some(Mode::Abs), Self::inc),
+            | 0xF6 => self.modify(Some(Mode::DpX), Self::inc),
+            | 0xFE => self.modify(Some(Mode::AbsX), Self::inc),
+            | 0x3A => self.modify(None, Self::dec),
+            | 0xC6 => self.modify(Some(Mode::Dp), Self::dec),
+            | 0xCE => self.modify(Some(Mode::Abs), Self::dec),
+            | 0xD6 => self.modify(Some(Mode::DpX), Self::dec),
+            | 0xDE => self.modify(Some(Mode::AbsX), Self::dec),
+            | 0x04 => self.modify(Some(Mode::Dp), Self::tsb),
+            | 0x0C => self.modify(Some(Mode::Abs), Self::tsb),
+            | 0x14 => self.modify(Some(Mode::Dp), Self::trb),
+            | 0x1C => self.modify(Some(Mode::Abs), Self::trb),
+
+            // Bit test.
+            | 0x89 =>
+            {
+                let value = self.load(Mode::Imm, m_wide);
+                self.flag(Z, self.acc() & value == 0);
+            },
+            | 0x24 | 0x2C | 0x34 | 0x3C =>
+            {
+                let mode = [Mode::Dp, Mode::Abs, Mode::DpX, Mode::AbsX][(op as usize >> 3) & 3];
+                let value = self.load(mode, m_wide);
+                let top = if m_wide { 0x8000 } else { 0x80 };
+                self.flag(Z, self.acc() & value == 0);
+                self.flag(N, value & top != 0);
+                self.flag(V, value & top >> 1 != 0);
+            },
+
+            // Loads, stores & compares of the index registers.
+            | 0xA2 => { let v = self.load(Mode::Imm, x_wide); self.x = self.index(v) },
+            | 0xA6 => { let v = self.load(Mode::Dp, x_wide); self.x = self.index(v) },
+            | 0xAE => { let v = self.load(Mode::Abs, x_wide); self.x = self.index(v) },
+            | 0xB6 => { let v = self.load(Mode::DpY, x_wide); self.x = self.index(v) },
+            | 0xBE => { let v = self.load(Mode::AbsY, x_wide); self.x = self.index(v) },
+            | 0xA0 => { let v = self.load(Mode::Imm, x_wide); self.y = self.index(v) },
+            | 0xA4 => { let v = self.load(Mode::Dp, x_wide); self.y = self.index(v) },
+            | 0xAC => { let v = self.load(Mode::Abs, x_wide); self.y = self.index(v) },
+            | 0xB4 => { let v = self.load(Mode::DpX, x_wide); self.y = self.index(v) },
+            | 0xBC => { let v = self.load(Mode::AbsX, x_wide); self.y = self.index(v) },
+            | 0x86 => self.store(Mode::Dp, self.x, x_wide),
+            | 0x8E => self.store(Mode::Abs, self.x, x_wide),
+            | 0x96 => self.store(Mode::DpY, self.x, x_wide),
+            | 0x84 => self.store(Mode::Dp, self.y, x_wide),
+            | 0x8C => self.store(Mode::Abs, self.y, x_wide),
+            | 0x94 => self.store(Mode::DpX, self.y, x_wide),
+            | 0x64 => self.store(Mode::Dp, 0, m_wide),
+            | 0x74 => self.store(Mode::DpX, 0, m_wide),
+            | 0x9C => self.store(Mode::Abs, 0, m_wide),
+            | 0x9E => self.store(Mode::AbsX, 0, m_wide),
+            | 0xE0 => { let v = self.load(Mode::Imm, x_wide); self.compare(self.x, v, x_wide) },
+            | 0xE4 => { let v = self.load(Mode::Dp, x_wide); self.compare(self.x, v, x_wide) },
+            | 0xEC => { let v = self.load(Mode::Abs, x_wide); self.compare(self.x, v, x_wide) },
+            | 0xC0 => { let v = self.load(Mode::Imm, x_wide); self.compare(self.y, v, x_wide) },
+            | 0xC4 => { let v = self.load(Mode::Dp, x_wide); self.compare(self.y, v, x_wide) },
+            | 0xCC => { let v = self.load(Mode::Abs, x_wide); self.compare(self.y, v, x_wide) },
+
+            // Index increments & decrements.
+            | 0xE8 => { self.cycles += 2; self.x = self.index(self.x.wrapping_add(1)) },
+            | 0xC8 => { self.cycles += 2; self.y = self.index(self.y.wrapping_add(1)) },
+            | 0xCA => { self.cycles += 2; self.x = self.index(self.x.wrapping_sub(1)) },
+            | 0x88 => { self.cycles += 2; self.y = self.index(self.y.wrapping_sub(1)) },
+
+            // Branches.
+            | 0x10 => self.branch(self.p & N == 0),
+            | 0x30 => self.branch(self.p & N != 0),
+            | 0x50 => self.branch(self.p & V == 0),
+            | 0x70 => self.branch(self.p & V != 0),
+            | 0x90 => self.branch(self.p & C == 0),
+            | 0xB0 => self.branch(self.p & C != 0),
+            | 0xD0 => self.branch(self.p & Z == 0),
+            | 0xF0 => self.branch(self.p & Z != 0),
+            | 0x80 => self.branch(true),
+            | 0x82 =>
+            {
+                let off = self.fetch16();
+                self.pc = self.pc.wrapping_add(off);
+                self.cycles += 4;
+            },
+
+            // Jumps, calls & returns.
+            | 0x4C => { self.pc = self.fetch16(); self.cycles += 3 },
+            | 0x5C =>
+            {
+                let addr = self.fetch24();
+                (self.pb, self.pc) = ((addr >> 16) as u8, addr as u16);
+                self.cycles += 4;
+            },
+            | 0x6C =>
+            {
+                let ptr = self.fetch16() as usize;
+                self.pc = self.read16(ptr);
+                self.cycles += 5;
+            },
+            | 0x7C =>
+            {
+                let ptr = (self.pb as usize) << 16 | self.fetch16().wrapping_add(self.x) as usize;
+                self.pc = self.read16(ptr);
+                self.cycles += 6;
+            },
+            | 0xDC =>
+            {
+                let ptr = self.fetch16() as usize;
+                let addr = self.read24(ptr);
+                (self.pb, self.pc) = ((addr >> 16) as u8, addr as u16);
+                self.cycles += 6;
+            },
+            | 0x20 =>
+            {
+                let addr = self.fetch16();
+                self.push16(self.pc.wrapping_sub(1));
+                self.pc = addr;
+                self.cycles += 6;
+            },
+            | 0xFC =>
+            {
+                let ptr = (self.pb as usize) << 16 | self.fetch16().wrapping_add(self.x) as usize;
+                self.push16(self.pc.wrapping_sub(1));
+                self.pc = self.read16(ptr);
+                self.cycles += 8;
+            },
+            | 0x22 =>
+            {
+                let addr = self.fetch24();
+                self.push(self.pb);
+                self.push16(self.pc.wrapping_sub(1));
+                (self.pb, self.pc) = ((addr >> 16) as u8, addr as u16);
+                self.cycles += 8;
+            },
+            | 0x60 => { self.pc = self.pull16().wrapping_add(1); self.cycles += 6 },
+            | 0x6B =>
+            {
+                self.pc = self.pull16().wrapping_add(1);
+                self.pb = self.pull();
+                self.cycles += 6;
+            },
+
+            // Stack.
+            | 0x48 => { self.cycles += 3 + m_wide as u64; if m_wide { self.push16(self.a) } else { self.push(self.a as u8) } },
+            | 0xDA => { self.cycles += 3 + x_wide as u64; if x_wide { self.push16(self.x) } else { self.push(self.x as u8) } },
+            | 0x5A => { self.cycles += 3 + x_wide as u64; if x_wide { self.push16(self.y) } else { self.push(self.y as u8) } },
+            | 0x68 => { self.cycles += 4 + m_wide as u64; let v = if m_wide { self.pull16() } else { self.pull() as u16 }; self.set_acc(v) },
+            | 0xFA => { self.cycles += 4 + x_wide as u64; let v = if x_wide { self.pull16() } else { self.pull() as u16 }; self.x = self.index(v) },
+            | 0x7A => { self.cycles += 4 + x_wide as u64; let v = if x_wide { self.pull16() } else { self.pull() as u16 }; self.y = self.index(v) },
+            | 0x08 => { self.cycles += 3; self.push(self.p) },
+            | 0x28 => { self.cycles += 4; let p = self.pull(); self.set_flags(p) },
+            | 0x8B => { self.cycles += 3; self.push(self.db) },
+            | 0xAB => { self.cycles += 4; self.db = self.pull(); self.set_nz(self.db as u16, false) },
+            | 0x0B => { self.cycles += 4; self.push16(self.d) },
+            | 0x2B => { self.cycles += 5; self.d = self.pull16(); self.set_nz(self.d, true) },
+            | 0x4B => { self.cycles += 3; self.push(self.pb) },
+            | 0xF4 => { self.cycles += 5; let v = self.fetch16(); self.push16(v) },
+            | 0xD4 => { self.cycles += 6; let ptr = self.dp(0); let v = self.read16(ptr); self.push16(v) },
+            | 0x62 => { self.cycles += 6; let off = self.fetch16(); self.push16(self.pc.wrapping_add(off)) },
+
+            // Flags.
+            | 0x18 => { self.cycles += 2; self.p &= !C },
+            | 0x38 => { self.cycles += 2; self.p |= C },
+            | 0x58 => { self.cycles += 2; self.p &= !I },
+            | 0x78 => { self.cycles += 2; self.p |= I },
+            | 0xD8 => { self.cycles += 2; self.p &= !D },
+            | 0xF8 => { self.cycles += 2; self.p |= D },
+            | 0xB8 => { self.cycles += 2; self.p &= !V },
+            | 0xC2 => { self.cycles += 3; let v = self.fetch(); self.set_flags(self.p & !v) },
+            | 0xE2 => { self.cycles += 3; let v = self.fetch(); self.set_flags(self.p | v) },
+            | 0xFB =>
+            {
+                self.cycles += 2;
+                let carry = self.p & C != 0;
+                self.flag(C, self.e);
+                self.e = carry;
+                self.fix_flags();
+            },
+
+            // Transfers.
+            | 0xAA => { self.cycles += 2; self.x = self.index(self.a) },
+            | 0xA8 => { self.cycles += 2; self.y = self.index(self.a) },
+            | 0x8A => { self.cycles += 2; self.set_acc(self.x) },
+            | 0x98 => { self.cycles += 2; self.set_acc(self.y) },
+            | 0x9B => { self.cycles += 2; self.y = self.index(self.x) },
+            | 0xBB => { self.cycles += 2; self.x = self.index(self.y) },
+            | 0xBA => { self.cycles += 2; self.x = self.index(self.s) },
+            | 0x9A => { self.cycles += 2; self.s = if self.e { 0x100 | self.x & 0xFF } else { self.x } },
+            | 0x5B => { self.cycles += 2; self.d = self.a; self.set_nz(self.d, true) },
+            | 0x7B => { self.cycles += 2; self.a = self.d; self.set_nz(self.a, true) },
+            | 0x1B => { self.cycles += 2; self.s = if self.e { 0x100 | self.a & 0xFF } else { self.a } },
+            | 0x3B => { self.cycles += 2; self.a = self.s; self.set_nz(self.a, true) },
+            | 0xEB => { self.cycles += 3; self.a = self.a.rotate_left(8); self.set_nz(self.a, false) },
+
+            // Block moves.
+            | 0x54 => self.block_move(1),
+            | 0x44 => self.block_move(0xFFFF),
+
+            // No operation.
+            | 0xEA => self.cycles += 2,
+            | 0x42 => { self.cycles += 2; self.fetch(); },
+
+            // Interrupts, waits & stops aren't needed by decompressors.
+            | _ => return Err(CpuOpcodeError(op, pc)),
+        };
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::Cpu;
+
+    // Assemble a routine at the start of bank C0.
+    fn rom(code: &[u8]) -> Vec<u8>
+    {
+        let mut rom = code.to_vec();
+        rom.resize(0x10000, 0);
+        rom
+    }
+
+    #[test]
+    fn call_copy_loop()
+    {
+        // Copy 4 bytes from $C0:0100 to $7F:0000 with 'LDA [$00],Y / STA [$03],Y'.
+        #[rustfmt::skip]
+        let mut rom = rom(&[
+            0xA0, 0x00,       // LDY #$00
+            0xB7, 0x00,       // LDA [$00],Y
+            0x97, 0x03,       // STA [$03],Y
+            0xC8,             // INY
+            0xC0, 0x04,       // CPY #$04
+            0xD0, 0xF7,       // BNE -9
+            0x6B,             // RTL
+        ]);
+        rom[0x100..0x104].copy_from_slice(&[1, 2, 3, 4]);
+        let mut cpu = Cpu::new(&rom);
+        cpu.write24(0x00, 0xC00100);
+        cpu.write24(0x03, 0x7F0000);
+        let cycles = cpu.call(0xC00000, true, 1000).unwrap();
+        assert_eq!((0..4).map(|i| cpu.read(0x7F0000 + i)).collect::<Vec<_>>(), [1, 2, 3, 4]);
+        assert_eq!(cycles, 2 + 4 * (6 + 6 + 2 + 2 + 3) - 1 + 6);
+    }
+
+    #[test]
+    fn call_jsr_wide()
+    {
+        // 16-bit add & subtract through a nested JSR, then a write through the WRAM port.
+        #[rustfmt::skip]
+        let rom = rom(&[
+            0xC2, 0x20,             // REP #$20
+            0xA9, 0xFF, 0x00,       // LDA #$00FF
+            0x20, 0x18, 0x00,       // JSR $0018
+            0x8F, 0x00, 0x00, 0x7F, // STA $7F0000
+            0xE2, 0x20,             // SEP #$20
+            0xA9, 0xAB,             // LDA #$AB
+            0x8F, 0x80, 0x21, 0x00, // STA $002180
+            0x60,                   // RTS
+            0x00, 0x00, 0x00,
+            0x18,                   // CLC
+            0x69, 0x02, 0x00,       // ADC #$0002
+            0x38,                   // SEC
+            0xE9, 0x01, 0x00,       // SBC #$0001
+            0x60,                   // RTS
+        ]);
+        let mut cpu = Cpu::new(&rom);
+        cpu.write(0x2181, 0x02);
+        cpu.write(0x2182, 0x00);
+        cpu.write(0x2183, 0x01);
+        cpu.call(0xC00000, false, 1000).unwrap();
+        assert_eq!(
+            [cpu.read(0x7F0000), cpu.read(0x7F0001), cpu.read(0x7F0002)],
+            [0x00, 0x01, 0xAB]
+        );
+        assert_eq!(cpu.a, 0x01AB);
+    }
+
+    #[test]
+    fn math()
+    {
+        let rom = rom(&[]);
+        let mut cpu = Cpu::new(&rom);
+        cpu.write(0x4202, 12);
+        cpu.write(0x4203, 34);
+        assert_eq!(cpu.read16(0x4216), 408);
+        cpu.write(0x4204, 0xE8);
+        cpu.write(0x4205, 0x03);
+        cpu.write(0x4206, 7);
+        assert_eq!(cpu.read16(0x4214), 142);
+        assert_eq!(cpu.read16(0x4216), 6);
+    }
+
+    #[test]
+    fn opcode_error()
+    {
+        let rom = rom(&[0xEA, 0xDB]); // NOP, STP
+        let mut cpu = Cpu::new(&rom);
+        let err = cpu.call(0xC00000, true, 1000).unwrap_err();
+        assert_eq!(err.to_string(), "CPU Error: unsupported opcode `0xDB` at `0xC00001`");
+    }
+
+    #[test]
+    fn limit_error()
+    {
+        let rom = rom(&[0x80, 0xFE]); // BRA -2
+        let mut cpu = Cpu::new(&rom);
+        let err = cpu.call(0xC00000, true, 1000).unwrap_err();
+        assert_eq!(err.to_string(), "CPU Error: routine did not return within 1000 cycles");
+    }
+}
@@ -0,0 +1,55 @@
+use std::collections::HashMap;
+
+// Lookup table of stored data for detecting duplicate entries. Entries are keyed on their full
+// byte content, so two streams only share storage when they are identical.
+#[derive(Clone, Debug, Default)]
+pub struct Dedup
+{
+    lookup: HashMap<Vec<u8>, Vec<usize>>, // data -> addresses holding it.
+}
+
+impl Dedup
+{
+    // Find the first address holding identical data that is accepted by the predicate.
+    pub fn find<F: Fn(usize) -> bool>(&self, data: &[u8], accept: F) -> Option<usize>
+    {
+        self.lookup.get(data)?.iter().copied().find(|&addr| accept(addr))
+    }
+
+    pub fn insert(&mut self, data: Vec<u8>, addr: usize)
+    {
+        self.lookup.entry(data).or_default().push(addr);
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::Dedup;
+
+    #[test]
+    fn find_eq()
+    {
+        let mut dedup = Dedup::default();
+        dedup.insert(vec![0; 257], 0xC00000);
+        assert_eq!(dedup.find(&[0; 257], |_| true), Some(0xC00000));
+    }
+
+    #[test]
+    fn find_ne()
+    {
+        let mut dedup = Dedup::default();
+        dedup.insert(vec![0; 254], 0xC00000);
+        assert_eq!(dedup.find(&[0; 256], |_| true), None);
+    }
+
+    #[test]
+    fn find_accept()
+    {
+        let mut dedup = Dedup::default();
+        dedup.insert(vec![1, 2, 3], 0xC00000);
+        dedup.insert(vec![1, 2, 3], 0xD00000);
+        assert_eq!(dedup.find(&[1, 2, 3], |addr| addr >= 0xD00000), Some(0xD00000));
+        assert_eq!(dedup.find(&[1, 2, 3], |addr| addr >= 0xE00000), None);
+    }
+}
@@ -0,0 +1,215 @@
+use crate::{
+    error::Error::{AplibDecodeError, AplibDecodeLimitError},
+    result::Result,
+};
+
+// Largest decompressed size accepted by default: all of WRAM.
+pub const MAX_SIZE: usize = 0x20000;
+
+// Token counts of a decoded stream, which drive the decode time.
+#[derive(Debug, Default, PartialEq)]
+pub struct Tokens
+{
+    pub literals:    usize, // Literal & single (4 bit offset) bytes.
+    pub matches:     usize,
+    pub match_bytes: usize,
+    pub bits:        usize, // Bits read from tag bytes.
+    pub bytes:       usize, // Stream bytes read, i.e. the compressed size.
+}
+
+// Independent, safe aPLib decoder for cross-validating apultra. Decodes a raw stream (without the
+// 0xFFFF prefix) given the dictionary it was compressed with, failing on any out of bounds read or
+// once the output would exceed the size limit.
+pub fn depack(input: &[u8], dict: &[u8], limit: usize) -> Result<Vec<u8>>
+{
+    Ok(depack_tokens(input, dict, limit)?.0)
+}
+
+// Decode a raw stream like 'depack' and also count its tokens.
+pub fn depack_tokens(input: &[u8], dict: &[u8], limit: usize) -> Result<(Vec<u8>, Tokens)>
+{
+    let mut src = 0;
+    let mut out = dict.to_vec();
+    let (mut tag, mut bits) = (0u8, 0);
+    let mut tokens = Tokens::default();
+
+    // Check that the decompressed size stays within the limit before adding to the output.
+    let check = |out: &Vec<u8>, len: usize| match out.len() - dict.len() + len > limit
+    {
+        | true => Err(AplibDecodeLimitError(limit)),
+        | false => Ok(()),
+    };
+
+    // Read the next byte, bit, or interlaced Elias-gamma number of the stream.
+    let byte = |src: &mut usize| -> Result<u8> {
+        let b = *input.get(*src).ok_or(AplibDecodeError(*src))?;
+        *src += 1;
+        Ok(b)
+    };
+    macro_rules! bit {
+        () => {{
+            if bits == 0
+            {
+                tag = byte(&mut src)?;
+                bits = 8;
+            }
+            bits -= 1;
+            tokens.bits += 1;
+            (tag >> bits) & 1 == 1
+        }};
+    }
+    macro_rules! gamma {
+        () => {{
+            let mut n = 1usize;
+            loop
+            {
+                n = (n << 1) + bit!() as usize;
+                if n > limit
+                {
+                    return Err(AplibDecodeError(src));
+                }
+                if !bit!()
+                {
+                    break n;
+                }
+            }
+        }};
+    }
+
+    check(&out, 1)?;
+    out.push(byte(&mut src)?); // first byte is always a literal.
+    tokens.literals += 1;
+    let (mut rep, mut lwm) = (0, false);
+    loop
+    {
+        let (offs, len) = if !bit!()
+        {
+            // Literal.
+            check(&out, 1)?;
+            out.push(byte(&mut src)?);
+            tokens.literals += 1;
+            lwm = false;
+            continue;
+        }
+        else if !bit!()
+        {
+            // Block match with an 8+n bit offset or a repeat of the last offset.
+            let hi = gamma!();
+            if !lwm && hi == 2
+            {
+                (rep, gamma!())
+            }
+            else
+            {
+                let offs = ((hi - if lwm { 2 } else { 3 }) << 8) + byte(&mut src)? as usize;
+                let len = gamma!()
+                    + (offs >= 32000) as usize
+                    + (offs >= 1280) as usize
+                    + if offs < 128 { 2 } else { 0 };
+                rep = offs;
+                (offs, len)
+            }
+        }
+        else if !bit!()
+        {
+            // Short match with a 7 bit offset, where an offset of 0 ends the stream.
+            let b = byte(&mut src)? as usize;
+            if b >> 1 == 0
+            {
+                tokens.bytes = src;
+                return Ok((out.split_off(dict.len()), tokens));
+            }
+            rep = b >> 1;
+            (b >> 1, 2 + (b & 1))
+        }
+        else
+        {
+            // Single byte with a 4 bit offset, where an offset of 0 writes a zero.
+            let mut offs = 0;
+            for _ in 0..4
+            {
+                offs = (offs << 1) | bit!() as usize;
+            }
+            let b = match offs
+            {
+                | 0 => 0,
+                | _ => out[out.len().checked_sub(offs).ok_or(AplibDecodeError(src))?],
+            };
+            check(&out, 1)?;
+            out.push(b);
+            tokens.literals += 1;
+            lwm = false;
+            continue;
+        };
+        lwm = true;
+
+        // Copy the match, which may overlap the bytes it produces.
+        check(&out, len)?;
+        let start =
+            out.len().checked_sub(offs).filter(|_| offs > 0).ok_or(AplibDecodeError(src))?;
+        for i in start..start + len
+        {
+            out.push(out[i]);
+        }
+        tokens.matches += 1;
+        tokens.match_bytes += len;
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::{depack, depack_tokens, Tokens};
+
+    #[test]
+    fn depack_zeros()
+    {
+        let input_data = vec![0, 173, 1, 86, 192, 0];
+        assert_eq!(depack(&input_data, &[], 100).unwrap(), [0; 100]);
+    }
+
+    #[test]
+    fn depack_tokens_zeros()
+    {
+        let input_data = vec![0, 173, 1, 86, 192, 0];
+        let (_, tokens) = depack_tokens(&input_data, &[], 100).unwrap();
+        assert_eq!(
+            tokens,
+            Tokens {
+                literals:    1,
+                matches:     1,
+                match_bytes: 99,
+                bits:        19,
+                bytes:       6,
+            }
+        );
+    }
+
+    #[test]
+    fn depack_limit_error()
+    {
+        let input_data = vec![0, 173, 1, 86, 192, 0];
+        let err = depack(&input_data, &[], 99).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Aplib Decompression Error: Decompressed size exceeds limit (99)"
+        );
+    }
+
+    #[test]
+    fn depack_truncated_error()
+    {
+        let input_data = vec![0, 173, 1];
+        let err = depack(&input_data, &[], 100).unwrap_err();
+        assert_eq!(err.to_string(), "Aplib Decompression Error: Corrupt stream at byte 3");
+    }
+
+    #[test]
+    fn depack_offset_error()
+    {
+        // Short match reaching back past the start of the output.
+        let input_data = vec![0x01, 0xC0, 0x09];
+        let err = depack(&input_data, &[], 100).unwrap_err();
+        assert_eq!(err.to_string(), "Aplib Decompression Error: Corrupt stream at byte 3");
+    }
+}
@@ -0,0 +1,167 @@
+use std::fmt;
+
+use crate::{result::Result, rom::Rom, workspace::Asset};
+
+// Asset that differs between two ROMs, missing from one of them if either side is None.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Difference
+{
+    pub field:  String,
+    pub index:  Option<usize>, // Table index, if the entry has a pointer table.
+    pub ours:   Option<Asset>,
+    pub theirs: Option<Asset>,
+}
+
+// Differences of two ROMs by asset. Assets whose decompressed content matches but whose compressed
+// streams don't are only listed under compression.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Diff
+{
+    pub content:     Vec<Difference>,
+    pub compression: Vec<Difference>,
+}
+
+impl Difference
+{
+    // Name of the asset, e.g. 'mapLayouts[23]'.
+    fn name(&self) -> String
+    {
+        match self.index
+        {
+            | Some(i) => format!("{}[{}]", self.field, i),
+            | None => self.field.clone(),
+        }
+    }
+}
+
+impl Diff
+{
+    pub fn is_empty(&self) -> bool
+    {
+        self.content.is_empty() && self.compression.is_empty()
+    }
+}
+
+impl fmt::Display for Diff
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result
+    {
+        // Decompressed size of an asset, or its compressed size & codec.
+        let len = |a: &Option<Asset>| a.as_ref().map_or("missing".into(), |a| a.len.to_string());
+        let size = |a: &Option<Asset>| {
+            a.as_ref().map_or("missing".into(), |a| format!("{} {}", a.codec.format(), a.size))
+        };
+
+        if !self.content.is_empty()
+        {
+            writeln!(f, "Content differences:")?;
+            for d in self.content.iter()
+            {
+                writeln!(f, " - {}: {} -> {} bytes", d.name(), len(&d.ours), len(&d.theirs))?;
+            }
+        }
+        if !self.compression.is_empty()
+        {
+            writeln!(f, "Compression differences:")?;
+            for d in self.compression.iter()
+            {
+                writeln!(f, " - {}: {} -> {} bytes", d.name(), size(&d.ours), size(&d.theirs))?;
+            }
+        }
+        let (content, compression) = (self.content.len(), self.compression.len());
+        write!(f, "{} asset(s) differ in content, {} in compression only", content, compression)
+    }
+}
+
+// Compare the assets of two ROMs, each decompressed through its own definition. Assets are matched
+// by entry & table index, as their addresses generally differ after recompression.
+pub fn diff(ours: &Rom, theirs: &Rom) -> Result<Diff>
+{
+    let (our_assets, their_assets) = (ours.export()?, theirs.export()?);
+    let key = |a: &Asset| (a.field.clone(), a.index);
+    let mut keys: Vec<_> = our_assets.iter().map(|(a, _)| key(a)).collect();
+    let added: Vec<_> =
+        their_assets.iter().map(|(a, _)| key(a)).filter(|k| !keys.contains(k)).collect();
+    keys.extend(added);
+
+    let mut diff = Diff::default();
+    for (field, index) in keys
+    {
+        let find = |assets: &[(Asset, Vec<u8>)]| {
+            assets.iter().find(|(a, _)| a.field == field && a.index == index).cloned()
+        };
+        let (our_asset, their_asset) = (find(&our_assets), find(&their_assets));
+        let content = match (&our_asset, &their_asset)
+        {
+            | (Some((a, data)), Some((b, other))) if data == other =>
+            {
+                match ours.stream(a) == theirs.stream(b)
+                {
+                    | true => continue,
+                    | false => false,
+                }
+            },
+            | _ => true,
+        };
+
+        #[rustfmt::skip]
+        let difference = Difference {
+            field, index, ours: our_asset.map(|(a, _)| a), theirs: their_asset.map(|(a, _)| a),
+        };
+        match content
+        {
+            | true => diff.content.push(difference),
+            | false => diff.compression.push(difference),
+        }
+    }
+    Ok(diff)
+}
+
+#[cfg(test)]
+mod tests
+{
+    use crate::{json::Config, rom::Rom};
+
+    #[test]
+    fn diff()
+    {
+        let test = r##"
+    {
+        "assembly": {
+            "a": {
+                "name": "A",
+                "range": "0xC00010-0xC0001C",
+                "arrayLength": 2,
+                "format": "ff6-lzss",
+                "pointerTable": { "range": "0xC00000-0xC00004", "offset": "0xC00000" }
+            },
+            "b": { "name": "B", "range": "0xC00020-0xC0002E", "format": "ff6-lzss" }
+        }
+    }"##;
+        let rom = |a1: u8, b: &[u8]| {
+            let mut bytes = vec![0x10, 0x00, 0x16, 0x00];
+            bytes.resize(0x10, 0);
+            bytes.extend([0x06, 0x00, 0x01, 0x11, 0xDE, 0x37]); // entry 0.
+            bytes.extend([0x06, 0x00, 0x01, a1, 0xDE, 0x37]); // entry 1.
+            bytes.resize(0x20, 0);
+            bytes.extend(b);
+            bytes.resize(0x40, 0);
+            Rom::with_config(bytes, Config::new(test).unwrap())
+        };
+        let ours = rom(0x22, &[0x06, 0x00, 0x01, 0x11, 0xDE, 0x37]);
+        let literals = [0x0E, 0x00, 0xFF, 0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x03];
+        let theirs = rom(0x33, &[&literals[..], &[0x11, 0x11]].concat());
+        let diff = super::diff(&ours, &theirs).unwrap();
+
+        // Entry b decompresses to the same data from a different stream.
+        let names = |d: &[super::Difference]| d.iter().map(|d| d.name()).collect::<Vec<_>>();
+        assert_eq!(names(&diff.content), ["a[1]"]);
+        assert_eq!(names(&diff.compression), ["b"]);
+        assert_eq!(
+            diff.to_string(),
+            "Content differences:\n - a[1]: 10 -> 10 bytes\nCompression differences:\n - b: \
+             ff6-lzss 6 -> ff6-lzss 14 bytes\n1 asset(s) differ in content, 1 in compression only"
+        );
+        assert!(super::diff(&ours, &ours).unwrap().is_empty());
+    }
+}
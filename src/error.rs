@@ -39,9 +39,68 @@ macro_rules! two_param_fn {
     }
 }
 
+// Location of the entry an error occurred in, e.g. "`mapLayouts` (Map Layouts) entry 23 at
+// 0xC4A000 (ROM offset 0x04A000)".
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Context
+{
+    pub field:  String,
+    pub name:   Option<String>,
+    pub index:  Option<usize>, // Table index, if the entry has a pointer table.
+    pub addr:   Option<usize>, // SNES address.
+    pub offset: Option<usize>, // ROM offset.
+}
+
+impl std::fmt::Display for Context
+{
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result
+    {
+        write!(f, "`{}`", self.field)?;
+        if let Some(name) = &self.name
+        {
+            write!(f, " ({})", name)?;
+        }
+        if let Some(index) = self.index
+        {
+            write!(f, " entry {}", index)?;
+        }
+        if let Some(addr) = self.addr
+        {
+            write!(f, " at {:#08X}", addr)?;
+        }
+        if let Some(offset) = self.offset
+        {
+            write!(f, " (ROM offset {:#08X})", offset)?;
+        }
+        Ok(())
+    }
+}
+
+impl Context
+{
+    // Fill in whatever this context lacks from a less specific one of the same entry.
+    fn merge(self, outer: Context) -> Context
+    {
+        Context {
+            name: self.name.or(outer.name),
+            index: self.index.or(outer.index),
+            addr: self.addr.or(outer.addr),
+            offset: self.offset.or(outer.offset),
+            ..self
+        }
+    }
+}
+
 #[derive(Error, Debug)]
 pub enum Error
 {
+    // Context Errors:
+    #[error("{context}: {source}")]
+    ContextError
+    {
+        context: Context, source: Box<Error>
+    },
+
     // Aplib Errors:
     #[error("Aplib {source}")] #[rustfmt::skip]
     AplibApultraError { #[from] source: apultra::Error },
@@ -49,8 +108,40 @@ pub enum Error
     AplibDecompressShortHeaderError(),
     #[error("Aplib Decompression Error: Invalid header")]
     AplibDecompressInvalidheaderError(),
+    #[error("Aplib Decompression Error: Corrupt stream at byte {0}")]
+    AplibDecodeError(usize),
+    #[error("Aplib Decompression Error: Decompressed size exceeds limit ({0})")]
+    AplibDecodeLimitError(usize),
+    #[error("Aplib Verification Error: {0} decoder output differs from the original data")]
+    AplibVerifyError(&'static str),
+    #[error("Aplib Dictionary Error: `{0}` entry {1} does not decode to its original data")]
+    AplibDictVerifyError(String, usize),
+
+    // Blob Errors:
+    #[error("Blob Error: `{0}` is an array with a pointer table")]
+    BlobTableError(String),
+    #[error("Blob Error: `{0}` range exceeds decompressed data size ({1}>{2})")]
+    BlobChildRangeError(String, usize, usize),
+    #[error("Blob Error: `{0}` replacement size differs from original ({1}!={2})")]
+    BlobChildSizeError(String, usize, usize),
+    #[error("Blob Error: `{0}` compressed size exceeds original range ({1}>{2})")]
+    BlobSizeError(String, usize, usize),
+
+    // Codec Errors:
+    #[error("Codec Error: unsupported codec `{0}` for this entry")]
+    CodecError(String),
+    #[error("Codec Error: `{0}` stream does not decode to the original data")]
+    CodecVerifyError(String),
+
+    // Size Errors:
+    #[error("Size Error: `{0}` entry {1} decompresses to {2} bytes, exceeding its buffer ({3})")]
+    DecompressSizeError(String, usize, usize, usize),
+    #[error("Size Error: `{0}` compressed size exceeds its range and it can't relocate ({1}>{2})")]
+    RangeSizeError(String, usize, usize),
 
     // LZSS Errors:
+    #[error("LZSS Compression Error: Compressed size exceeds its 16-bit length ({0}>0xFFFF)")]
+    LZSSCompressSizeError(usize),
     #[error("LZSS Decompression Error: Invalid compression length of 0")]
     LZSSDecompressZeroError(),
     #[error("LZSS Decompression Error: Input data too short (<2)")]
@@ -60,6 +151,14 @@ pub enum Error
     #[error("LZSS Decompression Error: Buffer length is less than decoded data size ({0}<{1})")]
     LZSSDecompressSizeError(usize, usize),
 
+    // CPU Errors:
+    #[error("CPU Error: unsupported opcode `{0:#04X}` at `{1:#08X}`")]
+    CpuOpcodeError(u8, usize),
+    #[error("CPU Error: decimal mode arithmetic at `{0:#08X}` is unsupported")]
+    CpuDecimalError(usize),
+    #[error("CPU Error: routine did not return within {0} cycles")]
+    CpuLimitError(u64),
+
     // From Errors:
     #[error("Error Parsing JSON: `{source}`")]  #[rustfmt::skip]
     FromJsonError { #[from] source: serde_json::Error },
@@ -71,18 +170,34 @@ pub enum Error
     ExtractPtrError(get_checked::Error),
     #[error("Splice Pointer Error: `{0}`")]
     SplicePtrError(get_checked::Error),
+    #[error("Splice Pointer Error: pointer `{0:#X}` exceeds pointer range of `{1:#X}`")]
+    SplicePtrOverflowError(usize, usize),
+    #[error("Pointer Error: `{0}` entry {1} is {2}")]
+    PtrPolicyError(String, usize, &'static str),
+    #[error("Relocation Error: no free space within reach for `{0}` ({1} bytes)")]
+    RelocateError(String, usize),
 
     // Zero Parameter Errors:
     #[error("Error Parsing: empty hex string")]
     HexEmptyError(),
 
     // Single Parameter Errors:
+    #[error("Argument Error: invalid value for `{0}`")]
+    ArgError(String),
     #[error("Error Parsing: failed to find JSON entry `{0}`")]
     JsonError(String),
     #[error("Error Parsing: invalid hex string `{0}`")]
     HexError(String),
     #[error("Error Parsing: invalid hex string range `{0}`")]
     HexRangeError(String),
+    #[error("Profile Error: unknown compression profile `{0}`")]
+    ProfileError(String),
+    #[error("Selector Error: invalid entry selector `{0}`")]
+    SelectorError(String),
+    #[error("Validation Error: range `{0}` ends before it begins")]
+    ValidateRangeOrderError(String),
+    #[error("Validation Error: pointer table `{0}` overlaps its data")]
+    ValidateTableOverlapError(String),
 
     // Two Parameter Errors:
     #[error("Error Parsing: number `0x{0}` too small to fit in target type for hex string `{1}`")]
@@ -93,7 +208,96 @@ pub enum Error
         "Error Parsing: number `0x{0}` number would be zero for non-zero type for hex string `{1}`"
     )]
     HexZeroError(String, String),
+    #[error("Validation Error: `{0}`: {1}")]
+    ValidateHexError(String, String),
+    #[error("Validation Error: range `{0}` overlaps `{1}`")]
+    ValidateOverlapError(String, String),
+    #[error("Validation Error: free space `{0}` overlaps `{1}`")]
+    ValidateFreeSpaceError(String, String),
+    #[error("Validation Error: range `{0}` extends past end of ROM (>{1})")]
+    ValidateOOBError(String, String),
 }
 nil_param_fn!(HexEmptyError);
-one_param_fn!(JsonError, HexError, HexRangeError);
-two_param_fn!(HexNegOverflowError, HexPosOverflowError, HexZeroError);
+one_param_fn!(
+    ArgError,
+    BlobTableError,
+    CodecError,
+    CodecVerifyError,
+    JsonError,
+    HexError,
+    HexRangeError,
+    ProfileError,
+    SelectorError,
+    ValidateRangeOrderError,
+    ValidateTableOverlapError
+);
+two_param_fn!(
+    HexNegOverflowError,
+    HexPosOverflowError,
+    HexZeroError,
+    ValidateHexError,
+    ValidateOverlapError,
+    ValidateFreeSpaceError,
+    ValidateOOBError
+);
+
+impl Error
+{
+    // Attach the entry an error occurred in. Contexts of the same entry are merged rather than
+    // chained, keeping the most specific details.
+    pub fn context(self, context: Context) -> Error
+    {
+        match self
+        {
+            | Error::ContextError { context: inner, source } if inner.field == context.field =>
+            {
+                Error::ContextError { context: inner.merge(context), source }
+            },
+            | e => Error::ContextError { context, source: Box::new(e) },
+        }
+    }
+}
+
+// Attach context to the error of a result, building it only on failure.
+pub trait WithContext<T>
+{
+    fn context<F: FnOnce() -> Context>(self, f: F) -> Result<T, Error>;
+}
+
+impl<T> WithContext<T> for Result<T, Error>
+{
+    fn context<F: FnOnce() -> Context>(self, f: F) -> Result<T, Error>
+    {
+        self.map_err(|e| e.context(f()))
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::{Context, Error, WithContext};
+
+    #[test]
+    fn context()
+    {
+        let ctx = |field: &str, index, addr| Context {
+            field: field.into(),
+            index,
+            addr,
+            offset: addr.map(|a| a & 0x3FFFFF),
+            ..Context::default()
+        };
+        let outer = Context { name: Some("Map Layouts".into()), ..ctx("mapLayouts", None, None) };
+        let err = Err::<(), _>(Error::LZSSDecompressZeroError())
+            .context(|| ctx("mapLayouts", Some(23), Some(0xC4A000)))
+            .context(|| outer)
+            .unwrap_err();
+
+        // Contexts of the same entry merge, while others chain.
+        let msg = "`mapLayouts` (Map Layouts) entry 23 at 0xC4A000 (ROM offset 0x04A000): LZSS \
+                   Decompression Error: Invalid compression length of 0";
+        assert_eq!(err.to_string(), msg);
+        let err = err.context(ctx("worldLayout1", None, None));
+        assert_eq!(err.to_string(), format!("`worldLayout1`: {}", msg));
+    }
+}
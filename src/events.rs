@@ -0,0 +1,188 @@
+use std::{
+    io::{stderr, stdout, IsTerminal, Write},
+    sync::Arc,
+};
+
+use crate::{error::Error, report::Report};
+
+// Progress of a run, reported to a listener instead of being printed by the library. Entries are
+// compressed ahead of being committed, so progress events come before their entry is started.
+#[derive(Debug)]
+#[rustfmt::skip]
+pub enum Event<'a>
+{
+    Begin      { rom_size: usize, entries: usize },
+    DryRun,
+    Started    { name: &'a str },
+    Progress   { field: &'a str, index: Option<usize>, processed: usize, compressed: usize },
+    Note       { message: String },
+    Warning    { field: &'a str, message: String },
+    Finished   { name: &'a str, profile: &'a str },
+    Failed     { field: &'a str, error: &'a Error },
+    Summary(&'a Report),
+}
+
+// Receiver of events. Shared with the compression threads, hence Send & Sync.
+pub type Listener = Arc<dyn Fn(&Event) + Send + Sync>;
+
+// Console renderer of events. Colors are only used on a terminal & never with NO_COLOR set, and
+// quiet mode only renders warnings & failures.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Console
+{
+    pub color: bool,
+    pub quiet: bool,
+}
+
+impl Console
+{
+    pub fn new(quiet: bool) -> Console
+    {
+        let no_color = std::env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty());
+        Console { color: !no_color && stdout().is_terminal(), quiet }
+    }
+
+    // Wrap text in an ANSI color, e.g. 33 for yellow, when colors are enabled.
+    fn paint(&self, color: u8, text: &str) -> String
+    {
+        match self.color
+        {
+            | true => format!("\x1b[{}m{}\x1b[39m", color, text),
+            | false => text.to_string(),
+        }
+    }
+
+    // Render a labelled value, e.g. "File Size (bytes): 3145728".
+    fn value(&self, label: &str, value: String) -> String
+    {
+        format!("{}{} {}\n", self.paint(33, label), self.paint(36, ":"), self.paint(32, &value))
+    }
+
+    // Render the failed entries & savings of a run.
+    fn summary(&self, report: &Report) -> String
+    {
+        let mut text = String::new();
+        if !report.failures.is_empty()
+        {
+            text += &format!("\n{}{}\n", self.paint(33, "Failed entries"), self.paint(36, ":"));
+            for (field, e) in report.failures.iter()
+            {
+                let (field, e) = (self.paint(36, field), self.paint(31, e));
+                text +=
+                    &format!(" {} {}{} {}\n", self.paint(33, "-"), field, self.paint(33, ":"), e);
+            }
+        }
+        text + "\n" + &self.value("Total savings (bytes)", report.saved_bytes.to_string())
+    }
+
+    // Render an event as the text written to stdout, if any.
+    pub fn render(&self, event: &Event) -> Option<String>
+    {
+        let text = match (event, self.quiet)
+        {
+            | (Event::Warning { field, message }, true) =>
+            {
+                format!("warning: `{}`: {}\n", field, message)
+            },
+            | (Event::Failed { field, error }, true) => format!("`{}` failed: {}\n", field, error),
+            | (_, true) => return None,
+            | (Event::Begin { rom_size, entries }, _) =>
+            {
+                let size = self.value("File Size (bytes)", rom_size.to_string());
+                let entries = self.value("Entries", entries.to_string());
+                let header = self.paint(33, "Recompressing") + &self.paint(36, ":");
+                format!("{}{}\n{}\n", size, entries, header)
+            },
+            | (Event::DryRun, _) => self.value("Dry run", "no changes are saved".into()),
+            | (Event::Started { name }, _) =>
+            {
+                let (dash, dots) = (self.paint(33, "-"), self.paint(33, "..."));
+                format!(" {} {}{}", dash, self.paint(36, name), dots)
+            },
+            | (Event::Progress { .. }, _) => return None,
+            | (Event::Note { message }, _) => format!("\n   {}", message),
+            | (Event::Warning { message, .. }, _) => format!("\n   warning: {}", message),
+            | (Event::Finished { name, profile }, _) =>
+            {
+                let pad = 55usize.saturating_sub(name.len());
+                let profile = format!("({})", profile);
+                format!("{:pad$}{} {}\n", "", self.paint(31, "done"), self.paint(36, &profile))
+            },
+            | (Event::Failed { .. }, _) => format!(" {}\n", self.paint(31, "failed")),
+            | (Event::Summary(report), _) => self.summary(report),
+        };
+        Some(text)
+    }
+
+    // Get a listener writing rendered events to stdout. Compression progress goes to stderr,
+    // and only when it's a terminal.
+    pub fn listener(self) -> Listener
+    {
+        let progress = !self.quiet && stderr().is_terminal();
+        Arc::new(move |event: &Event| {
+            if let Event::Progress { field, index, processed, compressed } = event
+            {
+                if progress
+                {
+                    let field = index.map_or(field.to_string(), |i| format!("{}[{}]", field, i));
+                    eprint!("\r\x1b[K{}: {} -> {} bytes\r", field, processed, compressed);
+                }
+                return;
+            }
+            if let Some(text) = self.render(event)
+            {
+                if progress
+                {
+                    eprint!("\r\x1b[K");
+                }
+                let mut out = stdout().lock();
+                let _ = out.write_all(text.as_bytes()).and_then(|_| out.flush());
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::{Console, Event};
+    use crate::{error::Error, report::Report};
+
+    #[test]
+    fn render()
+    {
+        let plain = Console { color: false, quiet: false };
+        let started = Event::Started { name: "A" };
+        assert_eq!(plain.render(&started).unwrap(), " - A...");
+        let finished = Event::Finished { name: "A", profile: "default" };
+        assert_eq!(plain.render(&finished).unwrap(), format!("{:54}done (default)\n", ""));
+
+        // Notes & warnings go on lines of their own below the started entry.
+        let note = Event::Note { message: "dictionary: 12 -> 8 bytes".into() };
+        assert_eq!(plain.render(&note).unwrap(), "\n   dictionary: 12 -> 8 bytes");
+        let warning = Event::Warning { field: "a", message: "6 >= 5".into() };
+        assert_eq!(plain.render(&warning).unwrap(), "\n   warning: 6 >= 5");
+        let report = Report { saved_bytes: 3, ..Report::default() };
+        assert_eq!(plain.render(&Event::Summary(&report)).unwrap(), "\nTotal savings (bytes): 3\n");
+
+        // Colors are only added when enabled.
+        let color = Console { color: true, quiet: false };
+        assert_eq!(
+            color.render(&started).unwrap(),
+            " \x1b[33m-\x1b[39m \x1b[36mA\x1b[39m\x1b[33m...\x1b[39m"
+        );
+    }
+
+    #[test]
+    fn render_quiet()
+    {
+        let quiet = Console { color: false, quiet: true };
+        assert_eq!(quiet.render(&Event::Started { name: "A" }), None);
+        let warning = Event::Warning { field: "a", message: "6 >= 5".into() };
+        assert_eq!(quiet.render(&warning).unwrap(), "warning: `a`: 6 >= 5\n");
+        let error = Error::LZSSDecompressZeroError();
+        let failed = Event::Failed { field: "a", error: &error };
+        let msg = "`a` failed: LZSS Decompression Error: Invalid compression length of 0\n";
+        assert_eq!(quiet.render(&failed).unwrap(), msg);
+    }
+}
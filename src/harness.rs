@@ -0,0 +1,61 @@
+use crate::{cpu::Cpu, json::Decoder, result::Result};
+
+// Cycles a single decoder call may take before giving up (several seconds on SNES).
+const LIMIT: u64 = 100_000_000;
+
+// Result of running the in-ROM decoder on one entry.
+#[derive(Debug, PartialEq)]
+pub struct Bench
+{
+    pub index:   usize,
+    pub format:  &'static str,
+    pub size:    usize, // Decompressed size.
+    pub cycles:  u64,
+    pub matches: bool, // Whether the output equals our own decompression.
+}
+
+// Run a decoder routine on compressed data at a SNES address, returning the first `len` bytes of
+// its output buffer along with the cycle count.
+pub fn run(rom: &[u8], decoder: &Decoder, src: usize, len: usize) -> Result<(Vec<u8>, u64)>
+{
+    let mut cpu = Cpu::new(rom);
+    cpu.set_flags(decoder.flags);
+    cpu.write24(decoder.source, src);
+    cpu.write24(decoder.dest, decoder.buffer);
+    let cycles = cpu.call(decoder.routine, decoder.long, LIMIT)?;
+
+    let output = (0..len).map(|i| cpu.read((decoder.buffer + i) & 0xFFFFFF)).collect();
+    Ok((output, cycles))
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::run;
+    use crate::json::Decoder;
+
+    #[test]
+    fn run_copy()
+    {
+        // Copy 4 bytes from the source to the destination pointer.
+        #[rustfmt::skip]
+        let mut rom = vec![
+            0xA0, 0x00, // LDY #$00
+            0xB7, 0x00, // LDA [$00],Y
+            0x97, 0x03, // STA [$03],Y
+            0xC8,       // INY
+            0xC0, 0x04, // CPY #$04
+            0xD0, 0xF7, // BNE -9
+            0x6B,       // RTL
+        ];
+        rom.resize(0x100, 0);
+        rom.extend([1, 2, 3, 4]);
+        #[rustfmt::skip]
+        let decoder = Decoder {
+            routine: 0xC00000, source: 0x00, dest: 0x03, buffer: 0x7F0000, long: true, flags: 0x30,
+        };
+        let (output, cycles) = run(&rom, &decoder, 0xC00100, 4).unwrap();
+        assert_eq!(output, [1, 2, 3, 4]);
+        assert_eq!(cycles, 83);
+    }
+}
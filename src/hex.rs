@@ -78,15 +78,20 @@ impl HexStringTo for &str
 
         let range = self.split("-").collect::<Vec<&str>>();
 
+        // Check that range consist of only two entries.
+        if range.len() != 2
+        {
+            return Err(HexRangeError(self));
+        };
+
         // Get range entries.
         let range0 = unsafe { range.get_unchecked(0) };
         let range1 = unsafe { range.get_unchecked(1) };
 
-        // Check that range consist of only two entries prefixed with 0x + >2 in length.
+        // Check that range entries are prefixed with 0x + >2 in length.
         #[rustfmt::skip]
-        if range.len() != 2
-            || !range0.starts_with("0x") || !range1.starts_with("0x")
-            ||  range0.len() < 3         ||  range1.len() < 3
+        if !range0.starts_with("0x") || !range1.starts_with("0x")
+            || range0.len() < 3         || range1.len() < 3
         {
             return Err(HexRangeError(self));
         };
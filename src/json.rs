@@ -1,25 +1,93 @@
 use std::{io::Write, ops::Range};
 
-use crate::{error::JsonError, hex::HexStringTo, result::Result, JsonError};
+use crate::{
+    aplib::{self, Profile},
+    codec::Codec,
+    error::{Error::DecompressSizeError, JsonError, ProfileError},
+    hex::HexStringTo,
+    lzss,
+    pointers::{PtrKind, PtrPolicy},
+    result::Result,
+    JsonError,
+};
 
 #[derive(Debug)]
 pub struct ExtractedData
 {
-    pub name:  String,
-    pub range: Range<usize>,
-    pub table: Option<PointerTable>,
+    pub name:     String,
+    pub range:    Range<usize>,
+    pub table:    Option<PointerTable>,
+    pub refs:     Vec<Reference>,
+    pub profile:  Option<String>, // Compression profile name.
+    pub budget:   Option<u64>,    // Maximum decode cycles per entry.
+    pub max_size: Option<usize>,  // Maximum decompressed size per entry, i.e. its WRAM buffer.
+    pub relocate: bool,           // Data may move into free space when it outgrows its range.
+    pub codecs:   Vec<String>,    // Codecs to compare, e.g. 'ff6-aplib:fast'. Empty for aPLib.
+    pub codec:    Option<Codec>,  // Codec of the data, as declared by its format.
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug, Default)]
 pub struct PointerTable
 {
-    pub range:    Range<usize>,
-    pub offset:   usize,
-    pub ptr_size: usize,
-    pub arr_len:  usize,
+    pub range:      Range<usize>,
+    pub offset:     usize,
+    pub ptr_size:   usize,
+    pub arr_len:    usize,
+    pub ptr_align:  usize,         // Pointer wrap boundary, e.g. 0x10000.
+    pub auto_bank:  bool,          // Bank is incremented whenever a pointer wraps.
+    pub bank_byte:  Option<usize>, // Start of a separate table of pointer bank bytes.
+    pub end_ptr:    bool,          // Table has a trailing pointer to the end of the data.
+    pub null_ptr:   Option<usize>, // Pointer value marking an empty entry.
+    pub invalid:    PtrPolicy,     // How pointers outside the data range are rebuilt.
+    pub interior:   PtrPolicy,     // How pointers into the data of another entry are rebuilt.
+    pub dict:       Dict,          // Dictionary entries are compressed against.
+    pub dict_range: Option<Range<usize>>, // Where the shared dictionary is stored, if it is.
+    pub refs:       Vec<Reference>,
 }
 
-#[derive(Debug)]
+// Dictionary prefix of table entries. 'Shared' holds the size of a single blob stored once in
+// ROM ahead of the entries, while 'Previous' uses the data of the preceding entry.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub enum Dict
+{
+    #[default]
+    None,
+    Shared(usize),
+    Previous,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct SubAssembly
+{
+    pub key:   String,
+    pub name:  String,
+    pub range: Range<usize>, // Range within the decompressed data.
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Reference
+{
+    pub addr:       usize, // Address of the code operand.
+    pub mask:       usize,
+    pub offset:     isize,
+    pub bank_byte:  bool, // Operand holds only the bank byte.
+    pub ptr_offset: bool, // Operand holds the pointer table offset.
+}
+
+// In-ROM decompression routine & its calling convention. Arguments are 24-bit pointers stored in
+// memory before the call.
+#[derive(Debug, PartialEq)]
+pub struct Decoder
+{
+    pub routine: usize,
+    pub source:  usize, // Address of the source pointer argument.
+    pub dest:    usize, // Address of the destination pointer argument.
+    pub buffer:  usize, // Destination buffer, e.g. 0x7F0000.
+    pub long:    bool,  // Called with JSL rather than JSR.
+    pub flags:   u8,    // Status flags on entry.
+}
+
+#[derive(Clone, Debug)]
 pub struct Config
 {
     config: serde_json::Value,
@@ -44,6 +112,125 @@ impl SerdeAs for serde_json::value::Value
     }
 }
 
+impl PointerTable
+{
+    // Get the rebuild policy of an invalid or interior pointer.
+    pub fn policy(&self, kind: PtrKind) -> Option<PtrPolicy>
+    {
+        match kind
+        {
+            | PtrKind::Invalid => Some(self.invalid),
+            | PtrKind::Interior(_) => Some(self.interior),
+            | _ => None,
+        }
+    }
+
+    // Check whether a table entry being packed at new_dp can point to data at a given address.
+    // Wrapping pointers can't go back from the last pointer, or they would move to the next bank.
+    pub fn can_point(&self, addr: usize, new_dp: usize, last_dp: usize) -> bool
+    {
+        let dp = match addr.checked_sub(self.offset)
+        {
+            | Some(dp) => dp,
+            | None => return false,
+        };
+        match (self.auto_bank, self.bank_byte)
+        {
+            | (true, _) => dp / self.ptr_align == new_dp / self.ptr_align && dp >= last_dp,
+            | (false, Some(_)) => dp < 1 << (8 * (self.ptr_size + 1)),
+            | (false, None) => dp < self.ptr_align.min(1 << (8 * self.ptr_size)),
+        }
+    }
+
+    // Check whether all the data of a table could be stored at an address.
+    pub fn can_hold(&self, addr: usize, len: usize) -> bool
+    {
+        let end = addr + len.max(1) - 1;
+        let dp = addr.wrapping_sub(self.offset);
+        self.can_point(addr, dp, dp) && self.can_point(end, end.wrapping_sub(self.offset), dp)
+    }
+
+    // Move the offset of the table to the bank of an address, for data relocated out of its reach.
+    pub fn rebased(&self, addr: usize) -> PointerTable
+    {
+        PointerTable { offset: addr & !0xFFFF, ..self.clone() }
+    }
+}
+
+impl ExtractedData
+{
+    // Check that the decompressed data of an entry fits within its buffer.
+    pub fn check_size(&self, i: usize, len: usize) -> Result<()>
+    {
+        match self.max_size
+        {
+            | Some(max_size) if len > max_size =>
+            {
+                Err(DecompressSizeError(self.name.clone(), i, len, max_size))
+            },
+            | _ => Ok(()),
+        }
+    }
+}
+
+// Decode the maximum decompressed size of an entry, either given as 'maxSize' or derived from
+// the dimensions of a tilemap ('width'×'height' tiles) or the length of an array of fixed size
+// elements. SNES tilemap formats (e.g. 'snes4bppTile') use 2 bytes per tile.
+fn extract_max_size(j_elem: &serde_json::Value) -> Result<Option<usize>>
+{
+    if let Some(s) = j_elem["maxSize"].as_str()
+    {
+        return Ok(Some(s.hex_to()?));
+    }
+
+    let formats = match &j_elem["format"]
+    {
+        | serde_json::Value::Array(formats) => formats.iter().filter_map(|f| f.as_str()).collect(),
+        | format => format.as_str().into_iter().collect::<Vec<_>>(),
+    };
+    let tile_size = match formats.iter().any(|f| f.starts_with("snes") && f.ends_with("Tile"))
+    {
+        | true => 2,
+        | false => 1,
+    };
+
+    let size = match j_elem["type"].as_str()
+    {
+        | Some("tilemap") => j_elem["width"]
+            .as_usize()
+            .zip(j_elem["height"].as_usize())
+            .map(|(width, height)| width * height * tile_size),
+        | Some("array") => j_elem["array"]["length"]
+            .as_usize()
+            .zip(j_elem["assembly"]["length"].as_usize())
+            .map(|(length, size)| length * size),
+        | _ => None,
+    };
+    Ok(size)
+}
+
+// Decode an array of code references, skipping those without an address (e.g. 'eval').
+fn extract_refs(j_refs: &serde_json::Value, path: &str) -> Result<Vec<Reference>>
+{
+    let mut refs = Vec::new();
+    for j_ref in j_refs.as_array().into_iter().flatten()
+    {
+        if let Some(addr) = j_ref["begin"].as_str()
+        {
+            let mask = j_ref["mask"].as_str().unwrap_or("0xFFFFFF").hex_to()?;
+            let offset = match j_ref.get("offset")
+            {
+                | Some(offset) => offset.as_i64().ok_or(JsonError!("{}/offset", path))? as isize,
+                | None => 0,
+            };
+            let bank_byte = j_ref["bankByte"].as_bool().unwrap_or(false);
+            let ptr_offset = j_ref["pointerOffset"].as_bool().unwrap_or(false);
+            refs.push(Reference { addr: addr.hex_to()?, mask, offset, bank_byte, ptr_offset });
+        }
+    }
+    Ok(refs)
+}
+
 impl Config
 {
     pub fn default() -> Config
@@ -56,6 +243,11 @@ impl Config
         Ok(Config { config: serde_json::from_str(input.as_ref())? })
     }
 
+    pub fn json(&self) -> &serde_json::Value
+    {
+        &self.config
+    }
+
     pub fn save<S: AsRef<str>>(&self, filename: S) -> Result<()>
     {
         let cnfg = serde_json::to_string_pretty(&self.config)?;
@@ -88,6 +280,31 @@ impl Config
             .ok_or(JsonError!("/assembly/{}/range", field))?
             .hex_to_range()?;
 
+        // Decode code references to the data.
+        let refs = extract_refs(&j_entry["reference"], &format!("/assembly/{}/reference", field))?;
+
+        // Decode compression profile name, e.g. 'fast'.
+        let profile = j_entry["compressionProfile"].as_string();
+
+        // Decode maximum decode time in CPU cycles.
+        let budget = j_entry["maxDecodeCycles"].as_u64();
+
+        // Decode maximum decompressed size. Array entries describe each element in 'assembly'.
+        let max_size = match (j_entry["maxSize"].as_str(), j_table.as_object())
+        {
+            | (Some(s), _) => Some(s.hex_to()?),
+            | (None, Some(_)) => extract_max_size(&j_entry["assembly"])?,
+            | (None, None) => extract_max_size(j_entry)?,
+        };
+
+        // Decode whether the data can be relocated.
+        let relocate = j_entry["canRelocate"].as_bool().unwrap_or(false);
+
+        // Decode the codecs to compare & the codec the data is currently stored with.
+        let codecs = j_entry["codecs"].as_array().into_iter().flatten();
+        let codecs = codecs.filter_map(|c| c.as_str()).map(String::from).collect();
+        let codec = j_entry["format"].as_str().and_then(Codec::from_format);
+
         // If pointer table exists, then this entry has an array of compressed sub-entries.
         // Check and return array length, pointer size, table offset, and table range.
         if let Some(_) = j_table.as_object()
@@ -115,15 +332,164 @@ impl Config
                 .ok_or(JsonError!("/assembly/{}/pointerTable/range", field))?
                 .hex_to_range()?; // Error if not parseable.
 
+            // Decode pointer alignment in the format of '0xYYYYYY'. It may be in either the entry
+            // or the table and defaults to the range of the pointer size.
+            let ptr_align = j_entry["pointerAlign"]
+                .as_str()
+                .or(j_table["pointerAlign"].as_str())
+                .map(|s| s.hex_to())
+                .transpose()?
+                .unwrap_or(1 << (8 * ptr_size));
+
+            // Decode bank wrapping and end pointer flags.
+            let auto_bank = j_entry["autoBank"].as_bool().unwrap_or(false);
+            let end_ptr = j_entry["endPointer"].as_bool().unwrap_or(false);
+
+            // Decode null pointer value in the format of '0xYYYY'.
+            let null_ptr = j_table["nullPointer"].as_str().map(|s| s.hex_to()).transpose()?;
+
+            // Decode rebuild policies of invalid and interior pointers, e.g. 'preserve'.
+            let policy = |key: &str, default: PtrPolicy| match j_table[key].as_str()
+            {
+                | Some(s) => PtrPolicy::from_str(s).ok_or(JsonError!(
+                    "/assembly/{}/pointerTable/{}",
+                    field,
+                    key
+                )),
+                | None => Ok(default),
+            };
+            let invalid = policy("invalidPointer", PtrPolicy::Empty)?;
+            let interior = policy("interiorPointer", PtrPolicy::Split)?;
+
+            // Decode dictionary mode, e.g. 'shared', and shared dictionary size as '0xYYYY'.
+            let dict_size = j_table["dictionarySize"].as_str().map(|s| s.hex_to()).transpose()?;
+            let dict = match j_table["dictionary"].as_str()
+            {
+                | Some("shared") => Dict::Shared(dict_size.unwrap_or(0x400)),
+                | Some("previous") => Dict::Previous,
+                | Some(_) => return Err(JsonError!("/assembly/{}/pointerTable/dictionary", field)),
+                | None => Dict::None,
+            };
+            let dict_range =
+                j_table["dictionaryRange"].as_str().map(|s| s.hex_to_range()).transpose()?;
+
+            // Decode bank byte table start address in the format of '0xYYYYYY'.
+            let bank_byte = j_table["bankByte"].as_str().map(|s| s.hex_to()).transpose()?;
+
+            // Decode code references to the table.
+            let path = format!("/assembly/{}/pointerTable/reference", field);
+            let tbl_refs = extract_refs(&j_table["reference"], &path)?;
+
             // Return entry with pointer table and array of compressed sub-entries.
-            let table = PointerTable { range: tbl_rnge, offset: tbl_offs, ptr_size, arr_len };
-            Ok(ExtractedData { name, range, table: Some(table) })
+            #[rustfmt::skip]
+            let table = PointerTable {
+                range: tbl_rnge, offset: tbl_offs, ptr_size, arr_len, ptr_align, auto_bank,
+                bank_byte, end_ptr, null_ptr, invalid, interior, dict, dict_range, refs: tbl_refs,
+            };
+            let table = Some(table);
+            #[rustfmt::skip]
+            let data = ExtractedData {
+                name, range, table, refs, profile, budget, max_size, relocate, codecs, codec,
+            };
+            Ok(data)
         }
         else
         {
             // Return entry without pointer table.
-            Ok(ExtractedData { name, range, table: None })
+            #[rustfmt::skip]
+            let data = ExtractedData {
+                name, range, table: None, refs, profile, budget, max_size, relocate, codecs, codec,
+            };
+            Ok(data)
+        }
+    }
+
+    // List every entry whose data (or nested assembly) is LZSS or aPLib compressed.
+    pub fn compressed(&self) -> Vec<String>
+    {
+        let is_compressed = |j: &serde_json::Value| match &j["format"]
+        {
+            | serde_json::Value::Array(formats) => formats.iter().any(|f| is_codec(f.as_str())),
+            | format => is_codec(format.as_str()),
+        };
+        fn is_codec(format: Option<&str>) -> bool
+        {
+            format == Some(lzss::FORMAT) || format == Some(aplib::FORMAT)
+        }
+
+        let j_assembly = self.config["assembly"].as_object().into_iter().flatten();
+        j_assembly
+            .filter(|(_, j_entry)| is_compressed(j_entry) || is_compressed(&j_entry["assembly"]))
+            .map(|(key, _)| key.to_string())
+            .collect()
+    }
+
+    // Look up the in-ROM decoder of a format under 'decoders', e.g. 'ff6-lzss'.
+    pub fn decoder<S: AsRef<str>>(&self, format: S) -> Result<Decoder>
+    {
+        let format = format.as_ref();
+        let j_dec = &self.config["decoders"][format];
+        j_dec.as_object().ok_or(JsonError!("/decoders/{}", format))?;
+
+        // Decode addresses in the format of '0xYYYYYY'.
+        let hex = |key: &str| -> Result<Option<usize>> {
+            j_dec[key].as_str().map(|s| s.hex_to()).transpose()
+        };
+        let required = |key: &str| hex(key)?.ok_or(JsonError!("/decoders/{}/{}", format, key));
+        let routine = required("routine")?;
+        let source = required("source")?;
+        let dest = required("destination")?;
+        let buffer = hex("buffer")?.unwrap_or(0x7F0000);
+        let flags = hex("flags")?.unwrap_or(0x30) as u8;
+
+        // Decode calling instruction, either 'jsl' (default) or 'jsr'.
+        let long = j_dec["call"].as_str() != Some("jsr");
+
+        Ok(Decoder { routine, source, dest, buffer, long, flags })
+    }
+
+    // Look up a compression profile defined under 'compressionProfiles' or else a built-in one.
+    pub fn profile<S: AsRef<str>>(&self, name: S) -> Result<Profile>
+    {
+        let name = name.as_ref();
+        let j_prof = &self.config["compressionProfiles"][name];
+        if j_prof.as_object().is_none()
+        {
+            return Profile::builtin(name).ok_or(ProfileError(name));
+        }
+
+        // Decode window and max offset in the format of '0xYYYY'. Both default to the full bank.
+        let hex = |key: &str| j_prof[key].as_str().map(|s| s.hex_to()).transpose();
+        let window = hex("window")?.unwrap_or(0x10000);
+        let max_offset = hex("maxOffset")?.unwrap_or(window);
+
+        // Decode apultra flags.
+        let flags = j_prof["flags"].as_u64().unwrap_or(0) as u32;
+
+        Ok(Profile { name: name.to_string(), window, max_offset, flags })
+    }
+
+    pub fn extract_assembly<S: AsRef<str>>(&self, field: S) -> Result<Vec<SubAssembly>>
+    {
+        let field = field.as_ref();
+
+        // Lookup nested assembly json and fail early if it isn't correct.
+        let j_assembly = self.config["assembly"][field]["assembly"]
+            .as_object()
+            .ok_or(JsonError(format!("/assembly/{}/assembly", field)))?;
+
+        // Decode every named child with a range in the format of '0xYYYY-0xZZZZ'.
+        let mut children = Vec::new();
+        for (key, j_child) in j_assembly.iter().filter(|(_, c)| c["range"].is_string())
+        {
+            let name = j_child["name"].as_string().unwrap_or(key.to_string());
+            let range = j_child["range"].as_str().unwrap_or_default().hex_to_range()?;
+            children.push(SubAssembly { key: key.to_string(), name, range });
         }
+
+        // Order children by their position in the decompressed data.
+        children.sort_by_key(|c| c.range.start);
+        Ok(children)
     }
 
     pub fn update<S: AsRef<str>>(&mut self, field: S, range: Range<usize>) -> Result<()>
@@ -142,12 +508,111 @@ impl Config
         // Return okay.
         Ok(())
     }
+
+    pub fn update_format<S: AsRef<str>>(&mut self, field: S, from: &str, to: &str) -> Result<()>
+    {
+        let field = field.as_ref();
+
+        // Get mutable JSON entry and fail early if it doesn't exist.
+        let j_entry = self.config["assembly"]
+            .get_mut(field)
+            .ok_or(JsonError(format!("/assembly/{}", field)))?;
+
+        // Replace codec identifier in the entry and every nested assembly block.
+        fn replace(json: &mut serde_json::Value, from: &str, to: &str)
+        {
+            for (key, value) in json.as_object_mut().into_iter().flatten()
+            {
+                match (key.as_str(), value)
+                {
+                    | ("format", serde_json::Value::String(format)) if format == from =>
+                    {
+                        *format = to.to_string();
+                    },
+                    | ("format", serde_json::Value::Array(formats)) =>
+                    {
+                        formats.iter_mut().filter(|f| *f == from).for_each(|f| *f = to.into());
+                    },
+                    | (_, value) => replace(value, from, to),
+                }
+            }
+        }
+        replace(j_entry, from, to);
+
+        Ok(())
+    }
+
+    pub fn update_table<S: AsRef<str>>(&mut self, field: S, table: &PointerTable) -> Result<()>
+    {
+        let field = field.as_ref();
+
+        // Get mutable pointer table JSON and fail early if it doesn't exist.
+        let j_table = self.config["assembly"][field]
+            .pointer_mut("/pointerTable")
+            .and_then(|t| t.as_object_mut())
+            .ok_or(JsonError(format!("/assembly/{}/pointerTable", field)))?;
+
+        // Compare against the current table range and only insert it if it changed.
+        let s_range = format!("{:#08X}-{:#08X}", table.range.start, table.range.end);
+        let range =
+            j_table.get("range").and_then(|r| r.as_str()).and_then(|r| r.hex_to_range().ok());
+        if range != Some(table.range.clone())
+        {
+            j_table.insert("range".to_string(), serde_json::json!(s_range));
+        }
+
+        // Compare against the current table offset (defaults to 0) and only insert it if it changed.
+        let s_offset = format!("{:#08X}", table.offset);
+        let offset = j_table.get("offset").and_then(|o| o.as_str()).unwrap_or("0x0").hex_to().ok();
+        if offset != Some(table.offset)
+        {
+            j_table.insert("offset".to_string(), serde_json::json!(s_offset));
+        }
+
+        Ok(())
+    }
+
+    // Record where the shared dictionary of a pointer table is stored.
+    pub fn update_dictionary<S: AsRef<str>>(&mut self, field: S, range: Range<usize>)
+        -> Result<()>
+    {
+        let field = field.as_ref();
+        let j_table = self.config["assembly"][field]
+            .pointer_mut("/pointerTable")
+            .and_then(|t| t.as_object_mut())
+            .ok_or(JsonError(format!("/assembly/{}/pointerTable", field)))?;
+        let s_range = format!("{:#08X}-{:#08X}", range.start, range.end);
+        j_table.insert("dictionaryRange".to_string(), serde_json::json!(s_range));
+        Ok(())
+    }
+
+    // Decode the free space ranges in the format of '0xYYYYYY-0xZZZZZZ'.
+    pub fn free_space(&self) -> Result<Vec<Range<usize>>>
+    {
+        let j_free = self.config["freeSpace"].as_array().into_iter().flatten();
+        j_free
+            .enumerate()
+            .map(|(i, j)| j.as_str().ok_or(JsonError!("/freeSpace/{}", i))?.hex_to_range())
+            .collect()
+    }
+
+    // Replace the free space ranges, dropping empty ones.
+    pub fn update_free_space(&mut self, free: &[Range<usize>])
+    {
+        let s_free: Vec<_> = free
+            .iter()
+            .filter(|r| !r.is_empty())
+            .map(|r| format!("{:#08X}-{:#08X}", r.start, r.end))
+            .collect();
+        self.config["freeSpace"] = serde_json::json!(s_free);
+    }
 }
 
 #[cfg(test)]
 mod tests
 {
-    use super::Config;
+    use super::{Config, Decoder, Dict, PointerTable, Reference, SubAssembly};
+    use crate::{aplib::Profile, pointers::PtrPolicy};
 
     #[test]
     fn config_default()
@@ -251,6 +716,322 @@ mod tests
         assert_eq!(table.offset, 0x0);
         assert_eq!(table.range, 0xABCDEF1F..0xFDEFAF2F);
         assert_eq!(table.ptr_size, 2);
+        assert_eq!(table.ptr_align, 0x10000);
+    }
+
+    #[test]
+    fn extract_pointer_table_options()
+    {
+        let test = r##"
+    {
+        "assembly": {
+            "CinematicProgram": {
+                "name": "SomeName",
+                "range": "0xCD0000-0xCEEFBB",
+                "pointerAlign": "0x10000",
+                "autoBank": true,
+                "endPointer": true,
+                "array": { "length": 12 },
+                "pointerTable": {
+                    "range": "0xCCE602-0xCD0000",
+                    "bankByte": "0xCCE000",
+                    "reference": [ { "begin": "0xC07FCA", "mask": "0xFFFFFF" } ]
+                },
+                "reference": [
+                    { "begin": "0xC07F72", "mask": "0xFF", "bankByte": true },
+                    { "begin": "0xC01904", "pointerOffset": true, "offset": -2 },
+                    { "target": "dialogBankIncrement", "mask": "0xFFFF" }
+                ]
+            }
+        }
+    }"##;
+        let config = Config::new(test as &str).unwrap();
+        let extracted = config.extract("CinematicProgram").unwrap();
+        assert_eq!(
+            extracted.refs,
+            [
+                Reference { addr: 0xC07F72, mask: 0xFF, bank_byte: true, ..Reference::default() },
+                Reference {
+                    addr: 0xC01904,
+                    mask: 0xFFFFFF,
+                    offset: -2,
+                    ptr_offset: true,
+                    ..Reference::default()
+                },
+            ]
+        );
+        let table = extracted.table.unwrap();
+        assert_eq!(table.ptr_align, 0x10000);
+        assert!(table.auto_bank);
+        assert!(table.end_ptr);
+        assert_eq!(table.bank_byte, Some(0xCCE000));
+        assert_eq!(
+            table.refs,
+            [Reference { addr: 0xC07FCA, mask: 0xFFFFFF, ..Reference::default() }]
+        );
+    }
+
+    #[test]
+    fn extract_assembly()
+    {
+        let test = r##"
+    {
+        "assembly": {
+            "CinematicProgram": {
+                "name": "SomeName",
+                "range": "0xEF114F-0xEF3250",
+                "isSequential": true,
+                "assembly": {
+                    "tileset": { "name": "Tileset", "range": "0x0400-0x0800" },
+                    "graphics": { "range": "0x0000-0x0400" },
+                    "type": "data"
+                }
+            }
+        }
+    }"##;
+        let config = Config::new(test as &str).unwrap();
+        let children = config.extract_assembly("CinematicProgram").unwrap();
+        assert_eq!(
+            children,
+            [
+                SubAssembly {
+                    key:   "graphics".into(),
+                    name:  "graphics".into(),
+                    range: 0x0000..0x0400,
+                },
+                SubAssembly {
+                    key:   "tileset".into(),
+                    name:  "Tileset".into(),
+                    range: 0x0400..0x0800,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn extract_assembly_error()
+    {
+        let test = r##"
+    {
+        "assembly": {
+            "CinematicProgram": { "name": "SomeName", "range": "0xEF114F-0xEF3250" }
+        }
+    }"##;
+        let config = Config::new(test as &str).unwrap();
+        let err = config.extract_assembly("CinematicProgram").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Error Parsing: failed to find JSON entry `/assembly/CinematicProgram/assembly`"
+        );
+    }
+
+    #[test]
+    fn extract_pointer_table_policy()
+    {
+        let test = r##"
+    {
+        "assembly": {
+            "CinematicProgram": {
+                "name": "SomeName",
+                "range": "0xD9D1B0-0xDDFFF2",
+                "arrayLength": 12,
+                "pointerTable": {
+                    "range": "0xD9CD90-0xD9D1AD",
+                    "nullPointer": "0xFFFF",
+                    "invalidPointer": "preserve",
+                    "interiorPointer": "error"
+                }
+            }
+        }
+    }"##;
+        let config = Config::new(test as &str).unwrap();
+        let table = config.extract("CinematicProgram").unwrap().table.unwrap();
+        assert_eq!(table.null_ptr, Some(0xFFFF));
+        assert_eq!(table.invalid, PtrPolicy::Preserve);
+        assert_eq!(table.interior, PtrPolicy::Error);
+    }
+
+    #[test]
+    fn extract_pointer_table_policy_error()
+    {
+        let test = r##"
+    {
+        "assembly": {
+            "CinematicProgram": {
+                "name": "SomeName",
+                "range": "0xD9D1B0-0xDDFFF2",
+                "arrayLength": 12,
+                "pointerTable": { "range": "0xD9CD90-0xD9D1AD", "invalidPointer": "other" }
+            }
+        }
+    }"##;
+        let config = Config::new(test as &str).unwrap();
+        let err = config.extract("CinematicProgram").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Error Parsing: failed to find JSON entry `/assembly/CinematicProgram/pointerTable/invalidPointer`"
+        );
+    }
+
+    #[test]
+    fn extract_pointer_table_dictionary()
+    {
+        let test = r##"
+    {
+        "assembly": {
+            "a": {
+                "name": "A",
+                "range": "0xD9D1B0-0xDDFFF2",
+                "arrayLength": 12,
+                "pointerTable": {
+                    "range": "0xD9CD90-0xD9D1AD",
+                    "dictionary": "shared",
+                    "dictionarySize": "0x100"
+                }
+            },
+            "b": {
+                "name": "B",
+                "range": "0xD9D1B0-0xDDFFF2",
+                "arrayLength": 12,
+                "pointerTable": { "range": "0xD9CD90-0xD9D1AD", "dictionary": "previous" }
+            },
+            "c": {
+                "name": "C",
+                "range": "0xD9D1B0-0xDDFFF2",
+                "arrayLength": 12,
+                "pointerTable": { "range": "0xD9CD90-0xD9D1AD", "dictionary": "other" }
+            }
+        }
+    }"##;
+        let mut config = Config::new(test as &str).unwrap();
+        assert_eq!(config.extract("a").unwrap().table.unwrap().dict, Dict::Shared(0x100));
+        assert_eq!(config.extract("b").unwrap().table.unwrap().dict, Dict::Previous);
+        let err = config.extract("c").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Error Parsing: failed to find JSON entry `/assembly/c/pointerTable/dictionary`"
+        );
+
+        config.update_dictionary("a", 0xD9D1B0..0xD9D2B0).unwrap();
+        let table = config.extract("a").unwrap().table.unwrap();
+        assert_eq!(table.dict_range, Some(0xD9D1B0..0xD9D2B0));
+        let j_table = &config.json()["assembly"]["a"]["pointerTable"];
+        assert_eq!(j_table["dictionaryRange"], "0xD9D1B0-0xD9D2B0");
+    }
+
+    #[test]
+    fn extract_max_size()
+    {
+        // Derived from tilemap dimensions and array lengths of the default definition.
+        let config = Config::default();
+        assert_eq!(config.extract("battleBackgroundLayout").unwrap().max_size, Some(0x800));
+        assert_eq!(config.extract("vectorApproachLayout").unwrap().max_size, Some(0x400));
+        assert_eq!(config.extract("mapTileProperties").unwrap().max_size, Some(0x200));
+
+        // Given for layouts decompressed into the 0x7F0000 buffer, i.e. a map layer & the world.
+        assert_eq!(config.extract("mapLayouts").unwrap().max_size, Some(0x4000));
+        assert_eq!(config.extract("worldLayout1").unwrap().max_size, Some(0x10000));
+        assert_eq!(config.extract("worldLayout3").unwrap().max_size, Some(0x10000));
+
+        let test = r##"
+    {
+        "assembly": {
+            "a": {
+                "name": "A",
+                "range": "0xD9D1B0-0xDDFFF2",
+                "arrayLength": 12,
+                "maxSize": "0x1000",
+                "assembly": { "type": "tilemap", "width": 32, "height": 32 },
+                "pointerTable": { "range": "0xD9CD90-0xD9D1AD" }
+            },
+            "b": { "name": "B", "range": "0xD9D1B0-0xDDFFF2", "maxSize": "0xZZ" }
+        }
+    }"##;
+        let config = Config::new(test as &str).unwrap();
+        let a = config.extract("a").unwrap();
+        assert_eq!(a.max_size, Some(0x1000));
+        a.check_size(3, 0x1000).unwrap();
+        let err = a.check_size(3, 0x1001).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Size Error: `A` entry 3 decompresses to 4097 bytes, exceeding its buffer (4096)"
+        );
+        let err = config.extract("b").unwrap_err();
+        assert_eq!(err.to_string(), "Error Parsing: invalid hex string `0xZZ`");
+    }
+
+    #[test]
+    fn compressed()
+    {
+        let test = r##"
+    {
+        "assembly": {
+            "a": { "name": "A", "range": "0xC00000-0xC00010", "format": "ff6-lzss" },
+            "b": { "name": "B", "range": "0xC00010-0xC00020", "format": ["snes4bpp", "ff6-aplib"] },
+            "c": { "name": "C", "range": "0xC00020-0xC00030", "assembly": { "format": "ff6-lzss" } },
+            "d": { "name": "D", "range": "0xC00030-0xC00040", "format": "snes4bpp" }
+        }
+    }"##;
+        let config = Config::new(test as &str).unwrap();
+        assert_eq!(config.compressed(), ["a", "b", "c"]);
+
+        let config = Config::default();
+        let compressed = config.compressed();
+        assert!(crate::rom::ENTRIES.iter().all(|e| compressed.iter().any(|c| c == e)));
+    }
+
+    #[test]
+    fn free_space()
+    {
+        let test = r##"{ "freeSpace": ["0xC00010-0xC00020", "0xC10000-0xC10000"] }"##;
+        let mut config = Config::new(test as &str).unwrap();
+        assert_eq!(config.free_space().unwrap(), [0xC00010..0xC00020, 0xC10000..0xC10000]);
+
+        config.update_free_space(&[0xC00018..0xC00020, 0xC10000..0xC10000]);
+        assert_eq!(config.json()["freeSpace"], serde_json::json!(["0xC00018-0xC00020"]));
+
+        let config = Config::new(r##"{ "freeSpace": [1] }"##).unwrap();
+        let err = config.free_space().unwrap_err();
+        assert_eq!(err.to_string(), "Error Parsing: failed to find JSON entry `/freeSpace/0`");
+    }
+
+    #[test]
+    fn can_hold()
+    {
+        let table = PointerTable {
+            offset: 0xC00000,
+            ptr_size: 2,
+            ptr_align: 0x10000,
+            ..PointerTable::default()
+        };
+        assert!(table.can_hold(0xC0FF00, 0x100));
+        assert!(!table.can_hold(0xC0FF00, 0x101));
+        assert!(!table.can_hold(0xBFFFFF, 0x10));
+        let table = PointerTable { auto_bank: true, ..table };
+        assert!(table.can_hold(0xC0FF00, 0x101));
+    }
+
+    #[test]
+    fn can_point()
+    {
+        let table = PointerTable {
+            offset: 0xE70000,
+            ptr_size: 2,
+            ptr_align: 0x10000,
+            ..PointerTable::default()
+        };
+        assert!(table.can_point(0xE7FFFF, 0, 0));
+        assert!(!table.can_point(0xE80000, 0, 0));
+        assert!(!table.can_point(0xE6FFFF, 0, 0));
+        let table = PointerTable { bank_byte: Some(0xC00000), ..table };
+        assert!(table.can_point(0xE80000, 0, 0));
+        let table = PointerTable { auto_bank: true, ..table };
+        assert!(table.can_point(0xE80000, 0x10010, 0x10000));
+        assert!(!table.can_point(0xE80000, 0x0FFFF, 0x0FFF0));
+
+        // Data in the same bank but before the last pointer would read as the next bank.
+        assert!(table.can_point(0xE80008, 0x10010, 0x10008));
+        assert!(!table.can_point(0xE80008, 0x10010, 0x10009));
     }
 
     #[test]
@@ -369,6 +1150,170 @@ mod tests
         assert_eq!(config, r##"{"assembly":{"CinematicProgram":{"range":"0xFFFFFF-0x000000"}}}"##);
     }
 
+    #[test]
+    fn update_format()
+    {
+        let test = r##"
+    {
+        "assembly": {
+            "CinematicProgram": {
+                "format": "ff6-lzss",
+                "assembly": {
+                    "format": ["snes4bpp", "ff6-lzss"],
+                    "palette": { "format": "bgr555" }
+                }
+            }
+        }
+    }"##;
+        let mut config = Config::new(test as &str).unwrap();
+        config.update_format("CinematicProgram", "ff6-lzss", "ff6-aplib").unwrap();
+        let config = format!("{}", config.config);
+        assert_eq!(
+            config,
+            r##"{"assembly":{"CinematicProgram":{"assembly":{"format":["snes4bpp","ff6-aplib"],"palette":{"format":"bgr555"}},"format":"ff6-aplib"}}}"##
+        );
+    }
+
+    #[test]
+    fn update_format_error()
+    {
+        let test = r##"
+    {
+        "assembly": {
+            "CinematicProgram": { "format": "ff6-lzss" }
+        }
+    }"##;
+        let mut config = Config::new(test as &str).unwrap();
+        let err = config.update_format("NotEntry", "ff6-lzss", "ff6-aplib").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Error Parsing: failed to find JSON entry `/assembly/NotEntry`"
+        );
+    }
+
+    #[test]
+    fn update_table()
+    {
+        let test = r##"
+    {
+        "assembly": {
+            "CinematicProgram": {
+                "pointerTable": { "range": "0x111111-0x333333", "offset": "0x222222" }
+            },
+            "CreditsGraphics": {
+                "pointerTable": { "range": "0x111111-0x333333" }
+            }
+        }
+    }"##;
+        let mut config = Config::new(test as &str).unwrap();
+        let table =
+            PointerTable { range: 0x111111..0x444444, offset: 0x222222, ..PointerTable::default() };
+        config.update_table("CinematicProgram", &table).unwrap();
+        let table =
+            PointerTable { range: 0x111111..0x333333, offset: 0x555555, ..PointerTable::default() };
+        config.update_table("CreditsGraphics", &table).unwrap();
+        let config = format!("{}", config.config);
+        assert_eq!(
+            config,
+            r##"{"assembly":{"CinematicProgram":{"pointerTable":{"offset":"0x222222","range":"0x111111-0x444444"}},"CreditsGraphics":{"pointerTable":{"offset":"0x555555","range":"0x111111-0x333333"}}}}"##
+        );
+    }
+
+    #[test]
+    fn update_table_error()
+    {
+        let test = r##"
+    {
+        "assembly": {
+            "CinematicProgram": { "range": "0x000000-0xFFFFFF" }
+        }
+    }"##;
+        let mut config = Config::new(test as &str).unwrap();
+        let table = PointerTable::default();
+        let err = config.update_table("CinematicProgram", &table).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Error Parsing: failed to find JSON entry `/assembly/CinematicProgram/pointerTable`"
+        );
+    }
+
+    #[test]
+    fn profile()
+    {
+        let test = r##"
+    {
+        "compressionProfiles": {
+            "short": { "window": "0x1000", "flags": 1 }
+        },
+        "assembly": {
+            "CinematicProgram": {
+                "name": "Cinematic Program",
+                "range": "0x000000-0xFFFFFF",
+                "compressionProfile": "short",
+                "maxDecodeCycles": 5000
+            }
+        }
+    }"##;
+        let mut config = Config::new(test as &str).unwrap();
+        let data = config.extract("CinematicProgram").unwrap();
+        assert_eq!(data.budget, Some(5000));
+        let name = data.profile.unwrap();
+        let profile = config.profile(&name).unwrap();
+        let expected = Profile {
+            name:       "short".into(),
+            window:     0x1000,
+            max_offset: 0x1000,
+            flags:      1,
+        };
+        assert_eq!(profile, expected);
+        assert_eq!(config.profile("fast").unwrap(), Profile::builtin("fast").unwrap());
+
+        let err = config.profile("other").unwrap_err();
+        assert_eq!(err.to_string(), "Profile Error: unknown compression profile `other`");
+    }
+
+    #[test]
+    fn decoder()
+    {
+        let test = r##"
+    {
+        "decoders": {
+            "ff6-lzss": { "routine": "0xC30000", "source": "0xF3", "destination": "0xF6" },
+            "ff6-aplib": {
+                "routine": "0xEE0000",
+                "source": "0x00",
+                "destination": "0x03",
+                "buffer": "0x7E8000",
+                "call": "jsr",
+                "flags": "0x00"
+            },
+            "other": { "routine": "0xC30000" }
+        }
+    }"##;
+        let config = Config::new(test as &str).unwrap();
+        #[rustfmt::skip]
+        let expected = Decoder {
+            routine: 0xC30000, source: 0xF3, dest: 0xF6, buffer: 0x7F0000, long: true, flags: 0x30,
+        };
+        assert_eq!(config.decoder("ff6-lzss").unwrap(), expected);
+        #[rustfmt::skip]
+        let expected = Decoder {
+            routine: 0xEE0000, source: 0x00, dest: 0x03, buffer: 0x7E8000, long: false, flags: 0x00,
+        };
+        assert_eq!(config.decoder("ff6-aplib").unwrap(), expected);
+
+        let err = config.decoder("other").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Error Parsing: failed to find JSON entry `/decoders/other/source`"
+        );
+        let err = config.decoder("none").unwrap_err();
+        assert_eq!(err.to_string(), "Error Parsing: failed to find JSON entry `/decoders/none`");
+
+        // The built-in definition ships the game's LZSS routine.
+        assert_eq!(Config::default().decoder("ff6-lzss").unwrap().routine, 0xC2FF6D);
+    }
+
     #[test]
     fn update_error()
     {
@@ -5484,6 +6429,7 @@ static CONFIG: &'static str = r##"{
             "range": "0xD9D1B0-0xDDFFF2",
             "endPointer": true,
             "canRelocate": true,
+            "maxSize": "0x4000",
             "array": {
                 "length": 350
             },
@@ -10966,6 +11912,7 @@ static CONFIG: &'static str = r##"{
             "range": "0xEED434-0xEF114F",
             "format": "ff6-lzss",
             "canRelocate": true,
+            "maxSize": "0x10000",
             "reference": [
                 {
                     "begin": "0xEEB20F",
@@ -10979,6 +11926,7 @@ static CONFIG: &'static str = r##"{
             "range": "0xEF6A56-0xEF9D17",
             "format": "ff6-lzss",
             "canRelocate": true,
+            "maxSize": "0x10000",
             "reference": [
                 {
                     "begin": "0xEEB224",
@@ -10992,6 +11940,7 @@ static CONFIG: &'static str = r##"{
             "range": "0xEF9D17-0xEFB631",
             "format": "ff6-lzss",
             "canRelocate": true,
+            "maxSize": "0x10000",
             "reference": [
                 {
                     "begin": "0xEEB227",
@@ -17635,6 +18584,9 @@ static CONFIG: &'static str = r##"{
         "0xEDFFA7-0xEDFFD0",
         "0xEEAF01-0xEEB200",
         "0xEFFBC8-0xEFFEF0"
-    ]
+    ],
+    "decoders": {
+        "ff6-lzss": { "routine": "0xC2FF6D", "source": "0xF3", "destination": "0xF6" }
+    }
 }
 "##;
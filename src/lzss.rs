@@ -1,12 +1,119 @@
-use std::cell::RefCell;
+use std::{cell::RefCell, collections::HashMap};
 
 use crate::{
     error::Error::{
-        LZSSDecompressInputError, LZSSDecompressOOBError, LZSSDecompressSizeError,
-        LZSSDecompressZeroError,
+        LZSSCompressSizeError, LZSSDecompressInputError, LZSSDecompressOOBError,
+        LZSSDecompressSizeError, LZSSDecompressZeroError,
     },
     result::Result,
 };
+
+// Definition format identifier for FF6 LZSS data.
+pub const FORMAT: &str = "ff6-lzss";
+
+// Ring buffer size & the position data starts at, and the range of match lengths.
+const WINDOW: usize = 0x800;
+const START: usize = 0x07DE;
+const MIN_MATCH: usize = 3;
+const MAX_MATCH: usize = 34;
+
+// Get the longest match at each position, as (ring buffer position, length). The ring buffer
+// starts out zeroed, so matches may reach back into zeros before the data.
+fn matches(input: &[u8]) -> Vec<Option<(usize, usize)>>
+{
+    let text: Vec<u8> = [&[0; WINDOW][..], input].concat();
+    let mut chains: HashMap<[u8; 3], Vec<usize>> = HashMap::new();
+    let mut found = vec![None; input.len()];
+    for k in 0..text.len()
+    {
+        if k >= WINDOW && k + MIN_MATCH <= text.len()
+        {
+            // Check earlier positions with the same 3 bytes, nearest first, until a longest match.
+            let key = [text[k], text[k + 1], text[k + 2]];
+            let limit = (text.len() - k).min(MAX_MATCH);
+            let mut best: Option<(usize, usize)> = None;
+            for &j in chains.get(&key).into_iter().flatten().rev().take_while(|&&j| j + WINDOW >= k)
+            {
+                let len = (0..limit).take_while(|&i| text[j + i] == text[k + i]).count();
+                if best.is_none_or(|(_, l)| len > l)
+                {
+                    best = Some((j, len));
+                }
+                if len == limit
+                {
+                    break;
+                }
+            }
+            found[k - WINDOW] = best.map(|(j, len)| ((START + j - WINDOW) & (WINDOW - 1), len));
+        }
+        if k + MIN_MATCH <= text.len()
+        {
+            chains.entry([text[k], text[k + 1], text[k + 2]]).or_default().push(k);
+        }
+    }
+    found
+}
+
+// Compress with an optimal parse, minimizing the encoded size of every literal (9 bits) & match
+// (17 bits) from the end of the data backwards.
+pub fn compress(input: &[u8]) -> Result<Vec<u8>>
+{
+    let matches = matches(input);
+    let n = input.len();
+    let mut cost = vec![0; n + 1];
+    let mut step = vec![1; n];
+    for k in (0..n).rev()
+    {
+        cost[k] = cost[k + 1] + 9;
+        let longest = matches[k].map_or(0, |(_, len)| len);
+        for len in MIN_MATCH..=longest
+        {
+            if cost[k + len] + 17 < cost[k]
+            {
+                cost[k] = cost[k + len] + 17;
+                step[k] = len;
+            }
+        }
+    }
+
+    // Emit groups of 8 literals (flag set) or matches, each group led by its flags.
+    let mut output = vec![0, 0];
+    let (mut flags_at, mut item, mut k) = (0, 0, 0);
+    while k < n
+    {
+        if item % 8 == 0
+        {
+            flags_at = output.len();
+            output.push(0);
+        }
+        match step[k]
+        {
+            | 1 =>
+            {
+                output[flags_at] |= 1 << (item % 8);
+                output.push(input[k]);
+            },
+            | len =>
+            {
+                let pos = matches[k].map_or(0, |(pos, _)| pos);
+                let w = pos | (len - MIN_MATCH) << 11;
+                output.extend([w as u8, (w >> 8) as u8]);
+            },
+        }
+        k += step[k];
+        item += 1;
+    }
+
+    // Store the length of the compressed data, including the length itself.
+    let length = output.len();
+    if length > 0xFFFF
+    {
+        return Err(LZSSCompressSizeError(length));
+    }
+    output[..2].copy_from_slice(&(length as u16).to_le_bytes());
+    Ok(output)
+}
+
 pub fn decompress(input: &[u8]) -> Result<(Vec<u8>, usize)>
 {
     // Check if the input is long enough to contain length bytes.
@@ -108,8 +215,8 @@ pub fn decompress(input: &[u8]) -> Result<(Vec<u8>, usize)>
 mod tests
 {
     use super::{
-        decompress, LZSSDecompressInputError, LZSSDecompressOOBError, LZSSDecompressSizeError,
-        LZSSDecompressZeroError,
+        compress, decompress, LZSSDecompressInputError, LZSSDecompressOOBError,
+        LZSSDecompressSizeError, LZSSDecompressZeroError,
     };
 
     #[test]
@@ -120,6 +227,21 @@ mod tests
         assert_eq!(&data[..], &[0x11; 10]);
     }
 
+    #[test]
+    fn compression()
+    {
+        assert_eq!(compress(&[0x11; 10]).unwrap(), [0x06, 0x00, 0x01, 0x11, 0xDE, 0x37]);
+        assert_eq!(compress(&[]).unwrap(), [0x02, 0x00]);
+
+        // Round trip data with matches in the leading zeros, overlapping & far back.
+        let mut data: Vec<u8> = (0..3000u32).map(|i| (i * 7 % 251) as u8).collect();
+        data.splice(0..0, [0; 40]);
+        data.extend_from_within(100..400);
+        let compressed = compress(&data).unwrap();
+        assert!(compressed.len() < data.len());
+        assert_eq!(decompress(&compressed).unwrap(), (data, compressed.len()));
+    }
+
     #[test]
     fn decompression_error_data_too_short()
     {
@@ -1,15 +1,30 @@
 #![feature(trait_alias)]
-#![feature(map_try_insert)]
-use std::{fs, io};
+use std::{env, fs, io};
 
 mod aplib;
+mod blob;
+mod cache;
+mod codec;
+mod cost;
+mod cpu;
+mod dedup;
+mod depack;
+mod diff;
 mod error;
-mod hash;
+mod events;
+mod harness;
 mod hex;
 mod json;
 mod lzss;
+mod parallel;
+mod pointers;
+mod report;
 mod result;
 mod rom;
+mod selector;
+mod symbols;
+mod validate;
+mod workspace;
 
 fn open(path: &str) -> Result<Vec<u8>, io::Error>
 {
@@ -19,14 +34,235 @@ fn open(path: &str) -> Result<Vec<u8>, io::Error>
     //file.read_to_string(&mut contents)?;
 }
 
+// Load a definition from file or fall back to the built-in one.
+fn definition(path: Option<&String>) -> Result<json::Config, error::Error>
+{
+    match path
+    {
+        | Some(path) => json::Config::new(fs::read_to_string(path)?),
+        | None => Ok(json::Config::default()),
+    }
+}
+
+// Apply the compression options given on the command line.
+fn options(rom: &mut rom::Rom, args: &[String]) -> Result<(), error::Error>
+{
+    // Render progress on the console, with only warnings & failures given '--quiet'.
+    let quiet = args.iter().any(|a| a == "--quiet");
+    rom.set_listener(Some(events::Console::new(quiet).listener()));
+
+    // Select the global compression profile, e.g. '--profile fast'.
+    if let Some(i) = args.iter().position(|a| a == "--profile")
+    {
+        rom.set_profile(args.get(i + 1).ok_or(error::ArgError("--profile"))?);
+    }
+
+    // Select the entries to recompress, e.g. '--select mapLayouts[0..50],world*,!cinematicProgram'.
+    if let Some(i) = args.iter().position(|a| a == "--select")
+    {
+        let selector = args.get(i + 1).ok_or(error::ArgError("--select"))?;
+        rom.set_selector(selector::Selector::new(selector)?);
+    }
+
+    // Skip failing entries & report them at the end rather than rolling back every entry.
+    rom.set_keep_going(args.iter().any(|a| a == "--keep-going"));
+
+    // Cross-validate every produced stream with the pure-Rust decoder.
+    rom.set_verify(args.iter().any(|a| a == "--verify"));
+
+    // Set the number of compression threads, e.g. '--threads 1' for a single-threaded run.
+    if let Some(i) = args.iter().position(|a| a == "--threads")
+    {
+        let threads = args.get(i + 1).and_then(|n| n.parse().ok());
+        rom.set_threads(threads.ok_or(error::ArgError("--threads"))?);
+    }
+
+    // Reuse compressed streams of earlier runs, e.g. '--cache .cache --cache-limit 64' (MiB).
+    if let Some(i) = args.iter().position(|a| a == "--cache")
+    {
+        let dir = args.get(i + 1).ok_or(error::ArgError("--cache"))?;
+        let limit = match args.iter().position(|a| a == "--cache-limit")
+        {
+            | Some(i) => args.get(i + 1).and_then(|n| n.parse::<u64>().ok()).map(|n| n << 20),
+            | None => Some(cache::LIMIT),
+        };
+        let limit = limit.ok_or(error::ArgError("--cache-limit"))?;
+        rom.set_cache(Some(cache::Cache::new(dir, limit)?));
+    }
+
+    // Compare codecs on entries without their own list, e.g. '--codecs ff6-aplib,ff6-lzss'.
+    if let Some(i) = args.iter().position(|a| a == "--codecs")
+    {
+        let codecs = args.get(i + 1).ok_or(error::ArgError("--codecs"))?;
+        rom.set_codecs(codecs.split(',').map(|c| c.trim().to_string()).collect());
+    }
+
+    // Limit the decode time of every entry, e.g. '--max-cycles 200000'.
+    if let Some(i) = args.iter().position(|a| a == "--max-cycles")
+    {
+        let budget = args.get(i + 1).and_then(|n| n.parse().ok());
+        rom.set_budget(Some(budget.ok_or(error::ArgError("--max-cycles"))?));
+    }
+    Ok(())
+}
+
+// Check whether to only project the results without saving anything, i.e. '--dry-run'.
+fn dry_run(args: &[String]) -> bool
+{
+    args.iter().any(|a| a == "--dry-run")
+}
+
+// Write the run report if requested, e.g. '--report sizes.csv'.
+fn save_report(report: &report::Report, args: &[String]) -> Result<(), error::Error>
+{
+    match args.iter().position(|a| a == "--report")
+    {
+        | Some(i) => report.save(args.get(i + 1).ok_or(error::ArgError("--report"))?),
+        | None => Ok(()),
+    }
+}
+
+// Write the relocation map & debugger symbols of the moved data if requested, e.g.
+// '--relocation-map map.json --symbols ff6'.
+fn save_symbols(
+    rom: &rom::Rom,
+    report: &report::Report,
+    args: &[String],
+) -> Result<(), error::Error>
+{
+    let map = rom.relocation_map(report);
+    if let Some(i) = args.iter().position(|a| a == "--relocation-map")
+    {
+        map.save(args.get(i + 1).ok_or(error::ArgError("--relocation-map"))?)?;
+    }
+    if let Some(i) = args.iter().position(|a| a == "--symbols")
+    {
+        map.save_symbols(args.get(i + 1).ok_or(error::ArgError("--symbols"))?)?;
+    }
+    Ok(())
+}
+
 // FIXME: Add checksum
 fn main()
 {
+    let args: Vec<String> = env::args().collect();
+
     let func = || -> Result<(), error::Error> {
-        let bytes = open("Final Fantasy III (USA) (Rev 1).sfc")?;
-        let mut rom = rom::Rom::new(bytes);
-        rom.process()?;
-        rom.save("test")?;
+        match args.get(1).map(String::as_str)
+        {
+            | Some("validate") =>
+            {
+                let config = definition(args.get(2))?;
+                let errors = validate::validate(&config);
+                for e in errors.iter()
+                {
+                    println!("{}", e);
+                }
+                println!("{} issue(s) found", errors.len());
+            },
+            | Some("bench") =>
+            {
+                let path = args.get(2).ok_or(error::ArgError("bench"))?;
+                let rom = rom::Rom::with_config(open(path)?, definition(args.get(3))?);
+                for field in rom::ENTRIES
+                {
+                    let (benches, skipped) = rom.bench(field)?;
+                    for b in benches
+                    {
+                        let check = if b.matches { "ok" } else { "MISMATCH" };
+                        #[rustfmt::skip]
+                        println!("{}/{:03}: {} {} bytes, {} cycles, {}", field, b.index, b.format, b.size, b.cycles, check);
+                    }
+                    for (index, e) in skipped
+                    {
+                        println!("{}/{:03}: skipped, {}", field, index, e);
+                    }
+                }
+            },
+            | Some("diff") =>
+            {
+                // e.g. 'diff OURS.sfc THEIRS.sfc [OUR_DEF [THEIR_DEF]]'.
+                let ours = args.get(2).ok_or(error::ArgError("diff"))?;
+                let theirs = args.get(3).ok_or(error::ArgError("diff"))?;
+                let ours = rom::Rom::with_config(open(ours)?, definition(args.get(4))?);
+                let theirs = rom::Rom::with_config(open(theirs)?, definition(args.get(5))?);
+                let diff = diff::diff(&ours, &theirs)?;
+                match diff.is_empty()
+                {
+                    | true => println!("no differences"),
+                    | false => println!("{}", diff),
+                }
+            },
+            | Some("export") =>
+            {
+                let bytes = open("Final Fantasy III (USA) (Rev 1).sfc")?;
+                let rom = rom::Rom::with_config(bytes, definition(args.get(3))?);
+                let dir = args.get(2).map_or("workspace", String::as_str);
+                let manifest = workspace::export(dir, &rom.export()?)?;
+                println!("{} asset(s) exported to `{}`", manifest.assets.len(), dir);
+            },
+            | Some("blob") =>
+            {
+                // e.g. 'blob cinematicProgram CHILD FILE [DEF]' to extract a child of the
+                // decompressed entry to FILE & with '--replace' to store FILE in its place.
+                let arg = |i: usize| args.get(i).ok_or(error::ArgError("blob"));
+                let (field, child, path) = (arg(2)?, arg(3)?, arg(4)?);
+                let bytes = open("Final Fantasy III (USA) (Rev 1).sfc")?;
+                let def = args.get(5).filter(|a| !a.starts_with("--"));
+                let mut rom = rom::Rom::with_config(bytes, definition(def)?);
+                let mut blob = rom.blob(field)?;
+                match args.iter().any(|a| a == "--replace")
+                {
+                    | true =>
+                    {
+                        blob.replace(child, &open(path)?)?;
+                        rom.store_blob(&blob)?;
+                        rom.save("rebuilt")?;
+                        rom.save_rom("rebuilt")?;
+                    },
+                    | false => fs::write(path, blob.child(child)?)?,
+                }
+            },
+            | Some("rebuild") =>
+            {
+                // e.g. 'rebuild BASE.sfc [DIR [DEF]]' to recompress the assets edited in DIR.
+                let path = args.get(2).ok_or(error::ArgError("rebuild"))?;
+                let arg = |i: usize| args.get(i).filter(|a| !a.starts_with("--"));
+                let mut rom = rom::Rom::with_config(open(path)?, definition(arg(4))?);
+                options(&mut rom, &args)?;
+                let dir = arg(3).map_or("workspace", String::as_str);
+                let run = |rom: &mut rom::Rom| rom.rebuild(dir);
+                let report = match dry_run(&args)
+                {
+                    | true => rom.dry_run(run)?,
+                    | false => run(&mut rom)?,
+                };
+                save_report(&report, &args)?;
+                save_symbols(&rom, &report, &args)?;
+                if !dry_run(&args)
+                {
+                    rom.save("rebuilt")?;
+                    rom.save_rom("rebuilt")?;
+                }
+            },
+            | _ =>
+            {
+                let bytes = open("Final Fantasy III (USA) (Rev 1).sfc")?;
+                let mut rom = rom::Rom::new(bytes);
+                options(&mut rom, &args)?;
+                let report = match dry_run(&args)
+                {
+                    | true => rom.dry_run(rom::Rom::process)?,
+                    | false => rom.process()?,
+                };
+                save_report(&report, &args)?;
+                save_symbols(&rom, &report, &args)?;
+                if !dry_run(&args)
+                {
+                    rom.save("test")?;
+                }
+            },
+        }
         Ok(())
     };
 
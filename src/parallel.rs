@@ -0,0 +1,63 @@
+use std::{
+    sync::atomic::{AtomicUsize, Ordering},
+    thread,
+};
+
+// Number of threads to use by default: one per available core.
+pub fn threads() -> usize
+{
+    thread::available_parallelism().map_or(1, |n| n.get())
+}
+
+// Map items on up to the given number of scoped threads. Threads take the next unclaimed item
+// until none are left, and results are returned in item order regardless of scheduling.
+pub fn map<T: Sync, R: Send, F: Fn(&T) -> R + Sync>(items: &[T], threads: usize, f: F) -> Vec<R>
+{
+    if threads <= 1 || items.len() <= 1
+    {
+        return items.iter().map(f).collect();
+    }
+
+    let next = AtomicUsize::new(0);
+    let done = thread::scope(|s| {
+        let workers: Vec<_> = (0..threads.min(items.len()))
+            .map(|_| {
+                s.spawn(|| {
+                    let mut done = Vec::new();
+                    loop
+                    {
+                        let i = next.fetch_add(1, Ordering::Relaxed);
+                        match items.get(i)
+                        {
+                            | Some(item) => done.push((i, f(item))),
+                            | None => break done,
+                        }
+                    }
+                })
+            })
+            .collect();
+        workers.into_iter().flat_map(|w| w.join().unwrap()).collect::<Vec<_>>()
+    });
+
+    // Put the results back in item order.
+    let mut results: Vec<Option<R>> = (0..items.len()).map(|_| None).collect();
+    for (i, result) in done
+    {
+        results[i] = Some(result);
+    }
+    results.into_iter().map(Option::unwrap).collect()
+}
+
+#[cfg(test)]
+mod tests
+{
+    #[test]
+    fn map()
+    {
+        let items: Vec<usize> = (0..100).collect();
+        let serial = super::map(&items, 1, |i| i * i);
+        assert_eq!(super::map(&items, 4, |i| i * i), serial);
+        assert_eq!(super::map(&items[..1], 4, |i| i * i), [0]);
+        assert_eq!(super::map(&[] as &[usize], 4, |i| i * i), [] as [usize; 0]);
+    }
+}
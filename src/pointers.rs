@@ -0,0 +1,137 @@
+use std::ops::Range;
+
+// Kind of an original table pointer. Recorded before repacking so that the same pointer graph can
+// be rebuilt afterwards.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum PtrKind
+{
+    Null,            // Table null value.
+    Data,            // Start of the entry's own data.
+    Alias(usize),    // Same pointer as an earlier entry.
+    Interior(usize), // Inside the data of another entry.
+    Invalid,         // Outside the table's data range.
+}
+
+// How invalid or interior pointers are rebuilt, selected per table in the definition.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub enum PtrPolicy
+{
+    #[default]
+    Empty, // Point to a shared empty entry.
+    Preserve, // Keep the original pointer.
+    Null,     // Replace with the table null value (or 0).
+    Split,    // Recompress the data at the pointer as an entry of its own.
+    Error,    // Fail.
+}
+
+impl PtrKind
+{
+    pub fn describe(&self) -> &'static str
+    {
+        match self
+        {
+            | PtrKind::Null => "a null pointer",
+            | PtrKind::Data => "a data pointer",
+            | PtrKind::Alias(_) => "an aliased pointer",
+            | PtrKind::Interior(_) => "an interior pointer",
+            | PtrKind::Invalid => "an invalid pointer",
+        }
+    }
+}
+
+impl PtrPolicy
+{
+    pub fn from_str(s: &str) -> Option<PtrPolicy>
+    {
+        match s
+        {
+            | "empty" => Some(PtrPolicy::Empty),
+            | "preserve" => Some(PtrPolicy::Preserve),
+            | "null" => Some(PtrPolicy::Null),
+            | "split" => Some(PtrPolicy::Split),
+            | "error" => Some(PtrPolicy::Error),
+            | _ => None,
+        }
+    }
+}
+
+// Classify data pointers given the length of the data at each pointer, the range of valid data
+// pointers, and the table null value (if any).
+pub fn classify(
+    dps: &[usize],
+    lens: &[usize],
+    valid: Range<usize>,
+    null: Option<usize>,
+) -> Vec<PtrKind>
+{
+    // Gather data spans of valid pointers ordered by start for interior lookups.
+    let mut spans = Vec::new();
+    for (i, &dp) in dps.iter().enumerate()
+    {
+        if Some(dp) != null && valid.contains(&dp)
+        {
+            spans.push((dp, dp + lens[i], i));
+        }
+    }
+    spans.sort();
+    spans.dedup_by_key(|&mut (start, ..)| start); // drop aliases.
+
+    let mut kinds = Vec::new();
+    for (i, &dp) in dps.iter().enumerate()
+    {
+        // Find the last span starting before the pointer.
+        let prev = spans.partition_point(|&(start, ..)| start < dp).checked_sub(1);
+        let inner = prev.map(|j| spans[j]).filter(|&(_, end, _)| dp < end);
+
+        let kind = match dps[..i].iter().position(|&other| other == dp)
+        {
+            | _ if Some(dp) == null => PtrKind::Null,
+            | Some(j) => PtrKind::Alias(j),
+            | None if !valid.contains(&dp) => PtrKind::Invalid,
+            | None => match inner
+            {
+                | Some((_, _, j)) => PtrKind::Interior(j),
+                | None => PtrKind::Data,
+            },
+        };
+        kinds.push(kind);
+    }
+    kinds
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::{classify, PtrKind, PtrPolicy};
+
+    #[test]
+    fn classify_kinds()
+    {
+        let dps = [0x00, 0x10, 0x10, 0x14, 0x40, 0x08, 0xFF];
+        let lens = [0x08, 0x08, 0x08, 0x02, 0x00, 0x08, 0x00];
+        let kinds = classify(&dps, &lens, 0x00..0x30, Some(0xFF));
+        assert_eq!(
+            kinds,
+            [
+                PtrKind::Data,
+                PtrKind::Data,
+                PtrKind::Alias(1),
+                PtrKind::Interior(1),
+                PtrKind::Invalid,
+                PtrKind::Data,
+                PtrKind::Null,
+            ]
+        );
+    }
+
+    #[test]
+    fn policy_from_str()
+    {
+        assert_eq!(PtrPolicy::from_str("empty"), Some(PtrPolicy::Empty));
+        assert_eq!(PtrPolicy::from_str("preserve"), Some(PtrPolicy::Preserve));
+        assert_eq!(PtrPolicy::from_str("null"), Some(PtrPolicy::Null));
+        assert_eq!(PtrPolicy::from_str("split"), Some(PtrPolicy::Split));
+        assert_eq!(PtrPolicy::from_str("error"), Some(PtrPolicy::Error));
+        assert_eq!(PtrPolicy::from_str("other"), None);
+    }
+}
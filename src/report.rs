@@ -0,0 +1,192 @@
+use std::time::Duration;
+
+use crate::{codec::Codec, result::Result};
+
+// Compression result of an entry, or of a single index of a pointer table entry.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Record
+{
+    pub field:       String,
+    pub index:       Option<usize>, // Table index, if the entry has a pointer table.
+    pub old_addr:    usize,
+    pub new_addr:    usize,
+    pub old_size:    usize, // Original compressed size (0 without data of its own).
+    pub new_size:    usize, // Stored compressed size (0 when shared or without data).
+    pub size:        usize, // Decompressed size.
+    pub codec:       Option<Codec>, // Codec of the stored data, if the index has data of its own.
+    pub profile:     Option<String>, // aPLib profile the stored data was compressed with.
+    pub dedup:       Option<usize>, // Address of identical data this index shares.
+    pub verified:    bool,  // Stored data was decoded & compared with the original.
+    pub cycles:      Option<u64>, // Estimated decode cycles of the stored data, given a budget.
+    pub over_budget: bool,  // Stored data exceeds the decode cycle budget.
+    pub time:        Duration, // Compression time.
+    pub candidates:  Vec<(String, usize)>, // Sizes of the other codecs compared.
+}
+
+// Per entry & index compression results of a run.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Report
+{
+    pub records:     Vec<Record>,
+    pub failures:    Vec<(String, String)>, // Skipped entries & their errors.
+    pub saved_bytes: usize,
+}
+
+impl Record
+{
+    fn to_json(&self) -> serde_json::Value
+    {
+        serde_json::json!({
+            "field": self.field,
+            "index": self.index,
+            "oldAddress": format!("{:#08X}", self.old_addr),
+            "newAddress": format!("{:#08X}", self.new_addr),
+            "oldSize": self.old_size,
+            "newSize": self.new_size,
+            "size": self.size,
+            "codec": self.codec.map(Codec::format),
+            "profile": self.profile,
+            "dedup": self.dedup.map(|addr| format!("{:#08X}", addr)),
+            "verified": self.verified,
+            "estimatedCycles": self.cycles,
+            "overBudget": self.over_budget,
+            "timeUs": self.time.as_micros() as u64,
+            "candidates": self
+                .candidates
+                .iter()
+                .map(|(codec, size)| serde_json::json!({ "codec": codec, "size": size }))
+                .collect::<Vec<_>>(),
+        })
+    }
+
+    fn to_csv(&self) -> String
+    {
+        let index = self.index.map_or(String::new(), |i| i.to_string());
+        let codec = self.codec.map_or("", Codec::format);
+        let profile = self.profile.as_deref().unwrap_or_default();
+        let cycles = self.cycles.map_or(String::new(), |cycles| cycles.to_string());
+        let dedup = self.dedup.map_or(String::new(), |addr| format!("{:#08X}", addr));
+        let candidates: Vec<_> =
+            self.candidates.iter().map(|(codec, size)| format!("{}={}", codec, size)).collect();
+        format!(
+            "{},{},{:#08X},{:#08X},{},{},{},{},{},{},{},{},{},{},{}",
+            self.field,
+            index,
+            self.old_addr,
+            self.new_addr,
+            self.old_size,
+            self.new_size,
+            self.size,
+            codec,
+            profile,
+            dedup,
+            self.verified,
+            cycles,
+            self.over_budget,
+            self.time.as_micros(),
+            candidates.join(";")
+        )
+    }
+}
+
+impl Report
+{
+    pub fn to_json(&self) -> serde_json::Value
+    {
+        let records: Vec<_> = self.records.iter().map(Record::to_json).collect();
+        let failures: Vec<_> = self
+            .failures
+            .iter()
+            .map(|(field, e)| serde_json::json!({ "field": field, "error": e }))
+            .collect();
+        serde_json::json!({ "savedBytes": self.saved_bytes, "entries": records, "failures": failures })
+    }
+
+    pub fn to_csv(&self) -> String
+    {
+        let mut csv = String::from(
+            "field,index,old_address,new_address,old_size,new_size,size,codec,profile,dedup,verified,estimated_cycles,over_budget,time_us,candidates\n",
+        );
+        for record in self.records.iter()
+        {
+            csv += &record.to_csv();
+            csv += "\n";
+        }
+        csv
+    }
+
+    // Write the report as CSV if the filename ends in '.csv' & as JSON otherwise.
+    pub fn save<S: AsRef<str>>(&self, filename: S) -> Result<()>
+    {
+        let filename = filename.as_ref();
+        let contents = match filename.ends_with(".csv")
+        {
+            | true => self.to_csv(),
+            | false => serde_json::to_string_pretty(&self.to_json())?,
+        };
+        std::fs::write(filename, contents)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use std::time::Duration;
+
+    use super::{Record, Report};
+    use crate::codec::Codec;
+
+    fn report() -> Report
+    {
+        #[rustfmt::skip]
+        let records = vec![
+            Record {
+                field: "a".into(), index: Some(0), old_addr: 0xC00010, new_addr: 0xC00010,
+                old_size: 6, new_size: 5, size: 10, codec: Some(Codec::Aplib),
+                profile: Some("fast".into()), dedup: None,
+                verified: true, cycles: Some(400), over_budget: false,
+                time: Duration::from_micros(12),
+                candidates: vec![("ff6-lzss".into(), 7), ("raw".into(), 10)],
+            },
+            Record {
+                field: "a".into(), index: Some(1), old_addr: 0xC00016, new_addr: 0xC00010,
+                old_size: 6, new_size: 0, size: 10, codec: None, profile: None,
+                dedup: Some(0xC00010), verified: false, cycles: None, over_budget: true,
+                time: Duration::ZERO, candidates: Vec::new(),
+            },
+        ];
+        let failures = vec![("b".into(), "Extract Pointer Error".into())];
+        Report { records, failures, saved_bytes: 1 }
+    }
+
+    #[test]
+    fn to_json()
+    {
+        let json = report().to_json();
+        assert_eq!(json["savedBytes"], 1);
+        assert_eq!(json["entries"][0]["codec"], "ff6-aplib");
+        assert_eq!(json["entries"][0]["profile"], "fast");
+        assert_eq!(json["entries"][0]["timeUs"], 12);
+        assert_eq!(json["entries"][0]["candidates"][0]["codec"], "ff6-lzss");
+        assert_eq!(json["entries"][0]["candidates"][1]["size"], 10);
+        assert_eq!(json["entries"][1]["dedup"], "0xC00010");
+        assert_eq!(json["entries"][1]["codec"], serde_json::Value::Null);
+        assert_eq!(json["entries"][0]["estimatedCycles"], 400);
+        assert_eq!(json["entries"][1]["estimatedCycles"], serde_json::Value::Null);
+        assert_eq!(json["entries"][1]["overBudget"], true);
+        assert_eq!(json["failures"][0]["field"], "b");
+    }
+
+    #[test]
+    fn to_csv()
+    {
+        let csv = report().to_csv();
+        let lines: Vec<_> = csv.lines().collect();
+        assert_eq!(lines.len(), 3);
+        let line =
+            "a,0,0xC00010,0xC00010,6,5,10,ff6-aplib,fast,,true,400,false,12,ff6-lzss=7;raw=10";
+        assert_eq!(lines[1], line);
+        assert_eq!(lines[2], "a,1,0xC00016,0xC00010,6,0,10,,,0xC00010,false,,true,0,");
+    }
+}
@@ -1,19 +1,69 @@
 use std::{
-    collections::HashMap,
-    io::{stdout, Write},
-    ops::AddAssign,
+    collections::{HashMap, HashSet},
+    fs,
+    ops::{AddAssign, Range},
+    path::Path,
+    time::{Duration, Instant},
 };
 
 use get_checked::GetChecked;
 
 use crate::{
-    aplib,
-    error::Error::{ExtractPtrError, SplicePtrError},
-    hash::HashOne,
-    json, lzss,
+    aplib::{self, Profile},
+    blob::Blob,
+    cache::{self, Cache},
+    codec::{self, Candidate, Codec},
+    cost,
+    dedup::Dedup,
+    error::{
+        BlobTableError, CodecError, CodecVerifyError, Context,
+        Error::{
+            AplibDictVerifyError, BlobSizeError, ExtractPtrError, PtrPolicyError, RangeSizeError,
+            RelocateError, SplicePtrError, SplicePtrOverflowError,
+        },
+        WithContext,
+    },
+    events::{Event, Listener},
+    harness::{self, Bench},
+    json::{self, Dict, ExtractedData, PointerTable, Reference},
+    lzss, parallel,
+    pointers::{self, PtrKind, PtrPolicy},
+    report::{Record, Report},
     result::Result,
+    selector::Selector,
+    symbols::Map,
+    workspace::{self, Asset, Manifest},
 };
-fn conv_addr(addr: usize) -> usize
+// Entries that are recompressed.
+pub const ENTRIES: [&str; 25] = [
+    "battleBackgroundGraphics",
+    "battleBackgroundLayout",
+    "cinematicProgram",
+    "creditsGraphics",
+    "endingGraphics",
+    "floatingIslandCinematic",
+    "mapAnimationGraphicsLayer3",
+    "mapGraphicsLayer3",
+    "mapLayouts",
+    "mapOverlayProperties",
+    "mapTileProperties",
+    "mapTilesets",
+    "worldGraphics3",
+    "worldLayout3",
+    "worldPalette3",
+    "titleIntroGraphics",
+    "vectorApproachGraphics",
+    "vectorApproachLayout",
+    "worldCloudsGraphics",
+    "worldCloudsLayout",
+    "worldGraphics1",
+    "worldLayout1",
+    "worldOfRuinCinematic",
+    "worldGraphics2",
+    "worldLayout2",
+];
+
+pub fn conv_addr(addr: usize) -> usize
 {
     if addr & 0x408000 != 0 { addr & 0x3FFFFF } else { 0x0 }
 }
@@ -30,8 +80,7 @@ impl TblPtr for [u8]
     fn splice_ptr(&mut self, r: TblEntry, ptr: usize) -> Result<()>
     {
         let mut ptr = ptr; // store mutable copy.
-        let entry =
-            &mut self.get_checked_mut(r.idx..r.idx + r.len).map_err(|e| SplicePtrError(e))?;
+        let entry = &mut self.get_checked_mut(r.idx..r.idx + r.len).map_err(SplicePtrError)?;
         for i in 0..r.len
         {
             entry[i] = ptr as u8; // store in big endian.
@@ -43,7 +92,7 @@ impl TblPtr for [u8]
     fn extract_ptr(&self, r: TblEntry) -> Result<usize>
     {
         let mut ptr: usize = 0; // store empty ptr.
-        let entry = self.get_checked(r.idx..r.idx + r.len).map_err(|e| ExtractPtrError(e))?;
+        let entry = self.get_checked(r.idx..r.idx + r.len).map_err(ExtractPtrError)?;
         for i in 0..r.len
         {
             let t = entry[i] as usize; // promote.
@@ -68,10 +117,203 @@ impl AddAssign<usize> for TblEntry
     }
 }
 
+// Pointer kind of a table index along with its decompressed data & asset description.
+type Decoded = (PtrKind, Result<(Asset, Vec<u8>)>);
+
+// Stored stream of an entry along with its estimated decode cycles & whether it's over budget.
+type Settled = ((Codec, Vec<u8>), Option<u64>, bool);
+
+// Table index skipped when benchmarking, along with the reason.
+type Skipped = (usize, String);
+
+// Original entry data: decompressed along with its original compressed stream. Edited data replaces
+// the decompressed data, so the original stream no longer matches it.
+struct Entry
+{
+    data:   Vec<u8>,
+    orig:   Vec<u8>,
+    codec:  Codec, // Codec of the original data.
+    edited: bool,
+}
+
+// Outcome of compressing an entry with a profile & its faster fallbacks while the modelled decode
+// time exceeds the budget. Computed without touching the ROM so entries can be compressed in
+// parallel.
+struct Attempt
+{
+    data:     Option<(Codec, Vec<u8>)>, // Chosen stream within the budget.
+    last:     (Codec, Vec<u8>),         // Stream of the last candidate tried otherwise.
+    tried:    Vec<String>,              // Candidates tried along with their size & cycles.
+    sizes:    Vec<(String, usize)>,     // Size of every candidate tried.
+    cycles:   Vec<u64>,                 // Estimated decode cycles of every candidate tried.
+    chosen:   Option<usize>,            // Candidate of the chosen stream.
+    isolated: usize,                    // Size when compressed without the dictionary, if measured.
+    time:     Duration,
+}
+
+// Entry data of a field gathered for recompression along with its compression attempts.
+struct Plan
+{
+    field:    String,
+    data:     ExtractedData,
+    profile:  Profile,
+    budget:   Option<u64>,
+    codecs:   Vec<Candidate>,     // Candidates in order of preference.
+    compare:  bool,               // Take the smallest candidate within budget, not the first.
+    entries:  Vec<Option<Entry>>, // A single entry unless the field has a pointer table.
+    selected: Vec<bool>,          // Entries to recompress. Others keep their original stream.
+    shared:   Vec<u8>,            // Shared dictionary of the table entries.
+    old_dps:  Vec<usize>,
+    kinds:    Vec<PtrKind>,
+    attempts: Vec<Option<Result<Attempt>>>,
+}
+
+impl Plan
+{
+    // Get the location of an entry for error messages.
+    fn context(&self, i: usize) -> Context
+    {
+        let index = self.data.table.as_ref().map(|_| i);
+        let addr = match &self.data.table
+        {
+            | Some(tbl) => self.old_dps.get(i).map(|dp| tbl.offset + dp),
+            | None => Some(self.data.range.start),
+        };
+        #[rustfmt::skip]
+        let context = Context {
+            field: self.field.clone(), name: Some(self.data.name.clone()), index, addr,
+            offset: addr.map(conv_addr),
+        };
+        context
+    }
+
+    // Get the dictionary an entry is compressed against.
+    fn dict(&self, i: usize) -> &[u8]
+    {
+        match self.data.table.as_ref().map_or(&Dict::None, |tbl| &tbl.dict)
+        {
+            | Dict::None => &[],
+            | Dict::Shared(_) => &self.shared,
+            | Dict::Previous =>
+            {
+                i.checked_sub(1).and_then(|j| self.entries[j].as_ref()).map_or(&[], |e| &e.data)
+            },
+        }
+    }
+
+    // Compress an entry with its candidates in turn, taking the first (or with 'compare', the
+    // smallest) stream whose modelled decode time is within the budget. Dictionary entries are
+    // also compressed in isolation for comparison.
+    fn attempt(
+        &self,
+        i: usize,
+        verify: bool,
+        cache: Option<&Cache>,
+        listener: Option<&Listener>,
+    ) -> Result<Attempt>
+    {
+        // Forward apultra's progress to the listener.
+        let index = self.data.table.as_ref().map(|_| i);
+        let progress = |processed: i64, compressed: i64| {
+            let (processed, compressed) = (processed.max(0) as usize, compressed.max(0) as usize);
+            let field = &self.field;
+            if let Some(l) = listener
+            {
+                l(&Event::Progress { field, index, processed, compressed });
+            }
+        };
+        let progress = listener.map(|_| &progress as &dyn Fn(i64, i64));
+
+        let time = Instant::now();
+        let (entry, dict, profile) =
+            (self.entries[i].as_ref().unwrap(), self.dict(i), &self.profile);
+
+        let mut tried = Vec::new();
+        let mut sizes = Vec::new();
+        let mut costs = Vec::new();
+        let mut recompressed: Option<(Codec, Vec<u8>)> = None;
+        let mut last = (Codec::Aplib, Vec::new());
+        let mut chosen = None;
+        for candidate in self.codecs.iter()
+        {
+            let (codec, dict) =
+                (candidate.codec(), if candidate.codec() == Codec::Aplib { dict } else { &[] });
+            let data = candidate.compress(&entry.data, dict, cache, progress)?;
+            match codec
+            {
+                | _ if !verify => (),
+                | Codec::Aplib => aplib::verify(&data, dict, &entry.data)?,
+                | _ if codec.decompress(&data)? != entry.data =>
+                {
+                    return Err(CodecVerifyError(candidate.to_string()));
+                },
+                | _ => (),
+            }
+            let cycles = match self.budget
+            {
+                | Some(_) => cost::codec_cycles(codec, &data, dict)?,
+                | None => 0,
+            };
+            tried.push(format!("{} {}/{}", candidate, data.len(), cycles));
+            sizes.push((candidate.to_string(), data.len()));
+            costs.push(cycles);
+            if self.budget.is_some_and(|budget| cycles > budget)
+            {
+                last = (codec, data);
+                continue;
+            }
+            if recompressed.as_ref().is_none_or(|(_, best)| data.len() < best.len())
+            {
+                recompressed = Some((codec, data));
+                chosen = Some(sizes.len() - 1);
+            }
+            if !self.compare
+            {
+                break;
+            }
+        }
+
+        let dict_mode = self.data.table.as_ref().is_some_and(|tbl| tbl.dict != Dict::None);
+        let isolated = match dict_mode
+        {
+            | true => cache::compress_dict(cache, &entry.data, &[], profile, progress)?.len(),
+            | false => 0,
+        };
+        #[rustfmt::skip]
+        let attempt = Attempt {
+            data: recompressed, last, tried, sizes, cycles: costs, chosen, isolated,
+            time: time.elapsed(),
+        };
+        Ok(attempt)
+    }
+}
+
+// Bank state of a table whose 16-bit pointers wrap into the next bank (autoBank).
+#[derive(Default)]
+struct TblBank
+{
+    bank: usize,
+    prev: usize,
+}
+
+#[derive(Clone)]
 pub struct Rom
 {
     rom:         Vec<u8>,
     config:      json::Config,
+    dedup:       Dedup,
+    profile:     String, // Compression profile of entries that don't select their own.
+    verify:      bool,   // Decode every produced stream, aPLib with both decoders.
+    budget:      Option<u64>, // Maximum decode cycles of entries that don't set their own.
+    edits:       HashMap<(String, Option<usize>), Vec<u8>>, // Edited data by field & table index.
+    records:     Vec<Record>, // Per entry & index results of recompressed entries.
+    keep_going:  bool,   // Skip failing entries instead of rolling back every entry.
+    failures:    Vec<(String, String)>, // Skipped entries & their errors.
+    threads:     usize,  // Threads compressing entries in parallel.
+    cache:       Option<Cache>, // Compressed streams of earlier runs.
+    selector:    Selector, // Entries & table indices to recompress.
+    listener:    Option<Listener>, // Receiver of progress events.
+    codecs:      Vec<String>, // Codecs to compare for entries that don't list their own.
     saved_bytes: usize,
 }
 
@@ -79,153 +321,2382 @@ impl Rom
 {
     pub fn new(bytes: Vec<u8>) -> Rom
     {
-        Rom { rom: bytes, saved_bytes: 0, config: json::Config::default() }
+        Rom::with_config(bytes, json::Config::default())
+    }
+
+    pub fn with_config(bytes: Vec<u8>, config: json::Config) -> Rom
+    {
+        let profile = Profile::default().name;
+        #[rustfmt::skip]
+        let rom = Rom {
+            rom: bytes, saved_bytes: 0, config, dedup: Dedup::default(), profile, verify: false,
+            budget: None, edits: HashMap::new(), records: Vec::new(), keep_going: false,
+            failures: Vec::new(), threads: parallel::threads(), cache: None,
+            selector: Selector::default(), listener: None, codecs: Vec::new(),
+        };
+        rom
     }
 
-    fn _recompress(&mut self, offset: usize) -> Result<Vec<u8>>
+    pub fn set_verify(&mut self, verify: bool)
     {
-        let (uncompressed, orig_compressed_size) =
-            lzss::decompress(&self.rom.get_checked(offset..).map_err(|e| ExtractPtrError(e))?)?;
-        let recompressed = aplib::compress(&uncompressed)?;
+        self.verify = verify;
+    }
 
-        if recompressed.len() > orig_compressed_size
+    pub fn set_codecs(&mut self, codecs: Vec<String>)
+    {
+        self.codecs = codecs;
+    }
+
+    pub fn set_listener(&mut self, listener: Option<Listener>)
+    {
+        self.listener = listener;
+    }
+
+    // Report an event to the listener, if any.
+    fn emit(&self, event: Event)
+    {
+        if let Some(listener) = &self.listener
         {
-            println!("warning: {} >= {}", recompressed.len(), orig_compressed_size);
+            listener(&event);
         }
-        else
+    }
+
+    pub fn set_selector(&mut self, selector: Selector)
+    {
+        self.selector = selector;
+    }
+
+    pub fn set_cache(&mut self, cache: Option<Cache>)
+    {
+        self.cache = cache;
+    }
+
+    pub fn set_threads(&mut self, threads: usize)
+    {
+        self.threads = threads;
+    }
+
+    pub fn set_keep_going(&mut self, keep_going: bool)
+    {
+        self.keep_going = keep_going;
+    }
+
+    pub fn set_budget(&mut self, budget: Option<u64>)
+    {
+        self.budget = budget;
+    }
+
+    pub fn set_profile<S: Into<String>>(&mut self, name: S)
+    {
+        self.profile = name.into();
+    }
+
+    // Resolve the compression profile of an entry, falling back to the global one.
+    fn profile(&self, data: &ExtractedData) -> Result<Profile>
+    {
+        self.config.profile(data.profile.as_ref().unwrap_or(&self.profile))
+    }
+
+    // Get the codecs to try for an entry & whether to compare them. Without any codecs listed,
+    // aPLib is tried with the entry's profile & then with faster profiles while over budget. Raw
+    // data needs code that copies it, so entries must list it themselves & have no pointer table.
+    fn candidates(&self, data: &ExtractedData, profile: &Profile)
+        -> Result<(Vec<Candidate>, bool)>
+    {
+        let specs = if data.codecs.is_empty() { &self.codecs } else { &data.codecs };
+        if specs.is_empty()
         {
-            let save = orig_compressed_size - recompressed.len();
-            self.saved_bytes += save;
+            let mut candidates = vec![Candidate::Aplib(profile.clone())];
+            if data.budget.or(self.budget).is_some()
+            {
+                let faster = ["fast", "near"].iter().filter_map(|name| Profile::builtin(name));
+                let faster = faster.filter(|p| p.max_offset < profile.max_offset);
+                candidates.extend(faster.map(Candidate::Aplib));
+            }
+            return Ok((candidates, false));
+        }
+
+        let mut candidates = Vec::new();
+        for spec in specs.iter()
+        {
+            let candidate = match spec.split_once(':')
+            {
+                | None if spec == aplib::FORMAT => Candidate::Aplib(profile.clone()),
+                | Some((format, name)) if format == aplib::FORMAT =>
+                {
+                    Candidate::Aplib(self.config.profile(name)?)
+                },
+                | None if spec == lzss::FORMAT => Candidate::Lzss,
+                | None if spec == codec::RAW
+                    && data.codecs.contains(spec)
+                    && data.table.is_none() =>
+                {
+                    Candidate::Raw
+                },
+                | None if spec == codec::RAW && !data.codecs.contains(spec) => continue,
+                | _ => return Err(CodecError(spec)),
+            };
+            candidates.push(candidate);
+        }
+        if candidates.is_empty()
+        {
+            return Err(CodecError(specs.join(",")));
+        }
+        Ok((candidates, true))
+    }
+
+    // Extract the data pointer (relative to the table offset) of a table entry.
+    fn extract_dp(&self, tbl: &PointerTable, i: usize, bank: &mut TblBank) -> Result<usize>
+    {
+        let mut tbl_entry = TblEntry { idx: conv_addr(tbl.range.start), len: tbl.ptr_size };
+        tbl_entry += i;
+        let mut dp = self.rom.extract_ptr(tbl_entry)?;
+
+        // Combine with the bank byte from the separate bank table.
+        if let Some(bank_byte) = tbl.bank_byte
+        {
+            let bank_entry = TblEntry { idx: conv_addr(bank_byte) + i, len: 1 };
+            dp |= self.rom.extract_ptr(bank_entry)? << (8 * tbl.ptr_size);
+        }
+
+        // Move to the next bank whenever the pointer wraps.
+        if tbl.auto_bank
+        {
+            if dp < bank.prev
+            {
+                bank.bank += tbl.ptr_align;
+            }
+            bank.prev = dp;
+            dp += bank.bank;
+        }
+
+        Ok(dp)
+    }
+
+    // Splice the data pointer (relative to the table offset) of a table entry.
+    fn splice_dp(&mut self, tbl: &PointerTable, i: usize, dp: usize) -> Result<()>
+    {
+        let mut tbl_entry = TblEntry { idx: conv_addr(tbl.range.start), len: tbl.ptr_size };
+        tbl_entry += i;
+
+        // Drop the bank for wrapping pointers.
+        let mut ptr = match tbl.auto_bank
+        {
+            | true => dp % tbl.ptr_align,
+            | false => dp,
         };
 
-        //let offset_end = offset + orig_compressed_size;
-        Ok(recompressed)
+        // Split off the bank byte into the separate bank table.
+        if let Some(bank_byte) = tbl.bank_byte
+        {
+            let bank_entry = TblEntry { idx: conv_addr(bank_byte) + i, len: 1 };
+            self.rom.splice_ptr(bank_entry, ptr >> (8 * tbl.ptr_size))?;
+            ptr &= (1 << (8 * tbl.ptr_size)) - 1;
+        }
+
+        // Check that the pointer fits within the pointer range.
+        let limit = tbl.ptr_align.min(1 << (8 * tbl.ptr_size));
+        if ptr >= limit
+        {
+            return Err(SplicePtrOverflowError(ptr, limit));
+        }
+
+        self.rom.splice_ptr(tbl_entry, ptr)
     }
 
-    #[rustfmt::skip]
-    pub fn recompress <S: AsRef<str>>(&mut self, json_entry: S) -> Result<()>
+    // Pack compressed table data at new_dp unless identical data within reach of the last pointer
+    // is already stored. Shareable data is recorded for use by later entries.
+    fn pack(
+        &mut self,
+        tbl: &PointerTable,
+        data: Vec<u8>,
+        (init_dp, new_dp, last_dp): (usize, &mut usize, usize),
+        share: bool,
+    ) -> usize
     {
-        // Extract data pointer logic.
-        let data = self.config.extract(&json_entry)?;
-        print!(" \x1b[33m-\x1b[36m {}\x1b[33m...\x1b[39m", data.name);
-        stdout().flush().unwrap();
-        let data_range = match data.table
+        // Look up identical data stored by a previous entry within reach. Only data packed into
+        // this table's range counts, so every pointer stays within the data range it owns.
+        let dp = *new_dp;
+        let packed = tbl.offset + init_dp..tbl.offset + dp;
+        let accept = |addr| packed.contains(&addr) && tbl.can_point(addr, dp, last_dp);
+        if let Some(addr) = self.dedup.find(&data, accept)
         {
-            | None =>       // single entry.
+            return addr - tbl.offset; // duplicate entry.
+        }
+
+        // Insert data into lookup table & splice in data.
+        let new_do = conv_addr(tbl.offset + dp);
+        let data_len = data.len();
+        if share
+        {
+            self.dedup.insert(data.clone(), tbl.offset + dp);
+        }
+        self.rom.splice(new_do..new_do + data_len, data);
+        *new_dp += data_len;
+        dp
+    }
+
+    // Splice the new target address into a code reference.
+    fn splice_ref(&mut self, r: &Reference, target: usize) -> Result<()>
+    {
+        let addr = (target as isize + r.offset) as usize;
+        let value = match r.bank_byte
+        {
+            | true => addr >> 16,
+            | false => addr,
+        };
+        let len = (usize::BITS - r.mask.leading_zeros() + 7) as usize / 8;
+        self.rom.splice_ptr(TblEntry { idx: conv_addr(r.addr), len }, value & r.mask)
+    }
+
+    // Update code references of any data, table, or table offset that moved.
+    fn splice_refs(
+        &mut self,
+        old: &ExtractedData,
+        range: &Range<usize>,
+        table: Option<&PointerTable>,
+    ) -> Result<()>
+    {
+        // Pair each reference with its old and new target.
+        let mut moved = Vec::new();
+        for r in old.refs.iter()
+        {
+            match (r.ptr_offset, &old.table, table)
             {
-                let bank_offset = data.range.start;
-                let offset      = conv_addr(bank_offset);
-                let data        = self._recompress(offset)?;
-                let data_len    = data.len();
-                let data_entry  = offset..offset + data_len;
-                self.rom.splice(data_entry, data);
-                bank_offset..bank_offset + data_len
+                | (true, Some(old_tbl), Some(tbl)) => moved.push((r, old_tbl.offset, tbl.offset)),
+                | _ => moved.push((r, old.range.start, range.start)),
             }
-            | Some(tbl) =>  // multiple entries.
+        }
+        if let (Some(old_tbl), Some(tbl)) = (&old.table, table)
+        {
+            for r in old_tbl.refs.iter()
             {
-                let mut tbl_entry = TblEntry { idx: conv_addr(tbl.range.start), len: tbl.ptr_size };
+                moved.push((r, old_tbl.range.start, tbl.range.start));
+            }
+        }
 
-                // extract init table data pointer for next entry & get next data ptrs.
-                let init_dp    = self.rom.extract_ptr(tbl_entry)?;
-                let mut old_dp = init_dp;
-                let mut new_dp = init_dp;
+        for (r, old_target, new_target) in moved
+        {
+            if old_target != new_target
+            {
+                self.splice_ref(r, new_target)?;
+            }
+        }
+        Ok(())
+    }
 
-                // Lookup table for detecting duplicate entries.
-                let mut lookup_tbl = HashMap::new();
+    // Get the location of an entry for error messages, looking up its display name.
+    fn context(&self, field: &str, index: Option<usize>, addr: Option<usize>) -> Context
+    {
+        #[rustfmt::skip]
+        let context = Context {
+            field: field.to_string(), name: self.config.extract(field).ok().map(|d| d.name), index,
+            addr, offset: addr.map(conv_addr),
+        };
+        context
+    }
 
-                for _ in 0..tbl.arr_len
-                {
-                    // Compute data offsets.
-                    let old_do = conv_addr(tbl.offset + old_dp);
-                    let new_do = conv_addr(tbl.offset + new_dp);
+    // Take the decompressed original data of an entry along with its original stream, replacing
+    // it with edited data if any.
+    fn entry_of(&self, field: &str, index: Option<usize>, decoded: (Asset, Vec<u8>)) -> Entry
+    {
+        let (asset, data) = decoded;
+        let offset = conv_addr(asset.addr);
+        let orig = self.rom[offset..offset + asset.size].to_vec();
+        self.edited(field, index, Entry { data, orig, codec: asset.codec, edited: false })
+    }
 
-                    // ensure old dp is after the initial.
-                    let data = match old_dp < init_dp
-                    {
-                        | true  => vec![0u8; 0],              // invalid pointer.
-                        | false => self._recompress(old_do)?, // valid pointer.
-                    };
+    // Get the original data of an entry stored as is, replacing it with edited data if any.
+    fn stored_at(&self, field: &str, range: Range<usize>) -> Result<Entry>
+    {
+        let offset = conv_addr(range.start);
+        let input = self.rom.get_checked(offset..offset + range.len());
+        let data = input.map_err(ExtractPtrError)?.to_vec();
+        let entry = Entry { data: data.clone(), orig: data, codec: Codec::Raw, edited: false };
+        Ok(self.edited(field, None, entry))
+    }
 
-                    // Hash newly compressed data.
-                    let hash = data.hash_one();
+    fn edited(&self, field: &str, index: Option<usize>, mut entry: Entry) -> Entry
+    {
+        if let Some(data) = self.edits.get(&(field.to_string(), index))
+        {
+            entry.data = data.clone();
+            entry.edited = true;
+        }
+        entry
+    }
 
-                    // Try to insert data into lookup table and get returned dp.
-                    let (data, dp) = match lookup_tbl.try_insert(hash, new_dp)
-                    {
-                        | Ok(value) => (data, *value),   // new entry.
-                        | Err(kv)   => (vec![0u8; 0], kv.value), // duplicate entry.
-                    };
+    // Find where data of a given length goes: in place while it fits within the original range, or
+    // else in free space accepted by the predicate & reachable by its code references. Data that
+    // outgrows the range of an entry that can't relocate is an error, as it would overwrite the
+    // data after it. The original range is released unless other pointers still use it.
+    fn place<F: Fn(usize) -> bool>(
+        &mut self,
+        data: &ExtractedData,
+        start: usize,
+        len: usize,
+        release: bool,
+        accept: F,
+    ) -> Result<usize>
+    {
+        if start + len <= data.range.end
+        {
+            return Ok(start);
+        }
+        if !data.relocate
+        {
+            let size = start + len - data.range.start;
+            return Err(RangeSizeError(data.name.clone(), size, data.range.len()));
+        }
+
+        // References holding only part of an address keep the rest of it, e.g. the bank.
+        let reachable = |addr: usize| {
+            let refs = data.refs.iter().filter(|r| !r.ptr_offset && !r.bank_byte);
+            refs.map(|r| !r.mask & 0xFFFFFF).all(|keep| addr & keep == data.range.start & keep)
+        };
+
+        // Take the first fit, trying the start of each free range and of every bank within it.
+        let mut free = self.config.free_space()?;
+        let found = free.iter().enumerate().find_map(|(i, r)| {
+            let banks = (r.start / 0x10000 + 1..).map(|b| b * 0x10000).take_while(|&s| s < r.end);
+            let mut starts = std::iter::once(r.start).chain(banks);
+            starts.find(|&s| s + len <= r.end && accept(s) && reachable(s)).map(|s| (i, s))
+        });
+        let (i, addr) = found.ok_or(RelocateError(data.name.clone(), len))?;
 
-                    // Splice in data (if any).
-                    let data_len   = data.len();
-                    let data_entry = new_do..new_do + data_len;
-                    self.rom.splice(data_entry, data);
-                    self.rom.splice_ptr(tbl_entry, dp)?; // splice in data ptr.
+        // Split the free range around the data & release the original range.
+        let r = free.remove(i);
+        free.insert(i, addr + len..r.end);
+        free.insert(i, r.start..addr);
+        if release
+        {
+            free.push(data.range.clone());
+            free.sort_by_key(|r| r.start);
+        }
+        self.config.update_free_space(&free);
+
+        let message =
+            format!("relocated: {:#08X} -> {:#08X} ({} bytes)", data.range.start, addr, len);
+        self.emit(Event::Note { message });
+        Ok(addr)
+    }
 
-                    // extract table pointer for next entry & get next data ptrs.
-                    tbl_entry += 1;
-                    old_dp     = self.rom.extract_ptr(tbl_entry)?;
-                    new_dp    += data_len;
+    // Settle the compression attempt of an entry, falling back to its original stream when no
+    // candidate met the budget, and report the size versus time tradeoff of any fallback or
+    // comparison. Also returns the estimated decode cycles of the settled stream given a budget,
+    // & whether it's over budget, as edited data has no original stream to fall back to & the
+    // original one may not meet it either.
+    fn settle(
+        &mut self,
+        field: &str,
+        entry: &Entry,
+        (compare, budget): (bool, Option<u64>),
+        attempt: Attempt,
+    ) -> Result<Settled>
+    {
+        let Attempt { data, last, tried, cycles: costs, chosen, .. } = attempt;
+        let orig_compressed_size = entry.orig.len();
+        let mut cycles = chosen.map(|j| costs[j]);
+        let ((codec, recompressed), message) = match data
+        {
+            | Some(data) if compare =>
+            {
+                let message = format!("codecs: {} bytes/cycles", tried.join(", "));
+                (data, Some(message))
+            },
+            | Some(data) if tried.len() == 1 => (data, None),
+            | Some(data) =>
+            {
+                let message = format!("budget: {} bytes/cycles", tried.join(" -> "));
+                (data, Some(message))
+            },
+            | None if entry.edited =>
+            {
+                let tried = tried.join(" -> ");
+                let message = format!("over budget: {} bytes/cycles (edited, no lzss)", tried);
+                self.emit(Event::Warning { field, message });
+                cycles = costs.last().copied();
+                (last, None)
+            },
+            | None =>
+            {
+                let orig_cycles = cost::codec_cycles(entry.codec, &entry.orig, &[])?;
+                let message = format!(
+                    "budget: {} -> lzss {}/{} bytes/cycles",
+                    tried.join(" -> "),
+                    orig_compressed_size,
+                    orig_cycles
+                );
+                if budget.is_some_and(|budget| orig_cycles > budget)
+                {
+                    let message = format!("over budget: lzss {} cycles", orig_cycles);
+                    self.emit(Event::Warning { field, message });
                 }
-                tbl.offset + init_dp..tbl.offset + new_dp
-            }
+                cycles = Some(orig_cycles);
+                ((entry.codec, entry.orig.clone()), Some(message))
+            },
         };
+        let cycles = budget.and(cycles);
+        let over_budget = cycles.zip(budget).is_some_and(|(cycles, budget)| cycles > budget);
+        if let Some(message) = message
+        {
+            self.emit(Event::Note { message });
+        }
 
-        // Insert updated json entry with new data range.
-        self.config.update(&json_entry, data_range)?;
+        if recompressed.len() > orig_compressed_size
+        {
+            let message = format!("{} >= {}", recompressed.len(), orig_compressed_size);
+            self.emit(Event::Warning { field, message });
+        }
+        else
+        {
+            let save = orig_compressed_size - recompressed.len();
+            self.saved_bytes += save;
+        };
 
-        println!("{:width$}\x1b[31mdone\x1b[39m", "", width = (55 - data.name.len()));
+        Ok(((codec, recompressed), cycles, over_budget))
+    }
 
-        Ok(())
+    // Gather the entry data of a field for recompression without modifying anything.
+    fn plan(&self, field: &str) -> Result<Plan>
+    {
+        let data = self.config.extract(field)?;
+        let profile = self.profile(&data)?;
+        let budget = data.budget.or(self.budget);
+        let (codecs, compare) = self.candidates(&data, &profile)?;
+        let Some(tbl) = &data.table
+        else
+        {
+            // Single entry, which is either compressed or stored as is across its range.
+            let context = || self.context(field, None, Some(data.range.start));
+            let entry = match data.codec == Some(Codec::Raw)
+            {
+                | true => self.stored_at(field, data.range.clone()),
+                | false =>
+                {
+                    let decoded = self.export_at(field, None, data.range.start, data.codec, &[]);
+                    decoded.map(|decoded| self.entry_of(field, None, decoded))
+                },
+            };
+            let entry = entry.context(context)?;
+            data.check_size(0, entry.data.len()).context(context)?;
+            #[rustfmt::skip]
+            let plan = Plan {
+                field: field.to_string(), data, profile, budget, codecs, compare,
+                entries: vec![Some(entry)],
+                selected: vec![true], shared: Vec::new(), old_dps: Vec::new(), kinds: Vec::new(),
+                attempts: vec![None],
+            };
+            return Ok(plan);
+        };
+
+        // Decompress every table pointer with the codec & dictionary of its own stream, as a table
+        // mixes LZSS & aPLib data after a partial run, & record the original pointer graph.
+        let (old_dps, decoded) = self.decode_table(field, &data, tbl)?;
+        let (kinds, decoded): (Vec<_>, Vec<_>) = decoded.into_iter().unzip();
+
+        // Decompress every entry before packing so no old data is overwritten early. An entry's
+        // dictionary is its predecessor, so aliases need data of their own then. Edited entries
+        // also need data of their own.
+        let mut entries = Vec::new();
+        for (i, (&kind, decoded)) in kinds.iter().zip(decoded).enumerate()
+        {
+            let context = || self.context(field, Some(i), Some(tbl.offset + old_dps[i]));
+            let entry_at = || decoded.map(|decoded| self.entry_of(field, Some(i), decoded));
+            let previous = tbl.dict == Dict::Previous;
+            let edited = self.edits.contains_key(&(field.to_string(), Some(i)));
+            let entry = match (kind, tbl.policy(kind))
+            {
+                | (_, Some(PtrPolicy::Error)) =>
+                {
+                    let e = PtrPolicyError(data.name.clone(), i, kind.describe());
+                    return Err(e.context(context()));
+                },
+                | (PtrKind::Data, _) | (_, Some(PtrPolicy::Split)) => Some(entry_at()),
+                | (PtrKind::Alias(_), _) if previous || edited => Some(entry_at()),
+                | (PtrKind::Interior(_), _) if edited => Some(entry_at()),
+                | _ => None,
+            };
+            entries.push(entry.transpose().context(context)?);
+        }
+
+        // Check that every entry still fits within its buffer.
+        for (i, entry) in entries.iter().enumerate()
+        {
+            let context = || self.context(field, Some(i), Some(tbl.offset + old_dps[i]));
+            entry.as_ref().map_or(Ok(()), |e| data.check_size(i, e.data.len())).context(context)?;
+        }
+
+        // Build the shared dictionary from the start of the entries.
+        let shared = match tbl.dict
+        {
+            | Dict::Shared(size) =>
+            {
+                aplib::dictionary(entries.iter().flatten().map(|e| &e.data[..]), size)
+            },
+            | _ => Vec::new(),
+        };
+
+        // Edited entries are always recompressed, whether selected or not.
+        let selected = (0..entries.len()).map(|i| {
+            self.edits.contains_key(&(field.to_string(), Some(i)))
+                || self.selector.selects_index(field, i)
+        });
+        let selected = selected.collect();
+
+        let attempts = entries.iter().map(|_| None).collect();
+        #[rustfmt::skip]
+        let plan = Plan {
+            field: field.to_string(), data, profile, budget, codecs, compare, entries, selected,
+            shared, old_dps, kinds, attempts,
+        };
+        Ok(plan)
     }
 
-    pub fn process(&mut self) -> Result<()>
-    {
-        let entries = [
-            "battleBackgroundGraphics",
-            "battleBackgroundLayout",
-            "cinematicProgram",
-            "creditsGraphics",
-            "endingGraphics",
-            "floatingIslandCinematic",
-            "mapAnimationGraphicsLayer3",
-            "mapGraphicsLayer3",
-            "mapLayouts",
-            "mapOverlayProperties",
-            "mapTileProperties",
-            "mapTilesets",
-            "worldGraphics3",
-            "worldLayout3",
-            "worldPalette3",
-            "titleIntroGraphics",
-            "vectorApproachGraphics",
-            "vectorApproachLayout",
-            "worldCloudsGraphics",
-            "worldCloudsLayout",
-            "worldGraphics1",
-            "worldLayout1",
-            "worldOfRuinCinematic",
-            "worldGraphics2",
-            "worldLayout2",
-        ];
-
-        println!("\x1b[33mFile Size (bytes)\x1b[36m: \x1b[32m{}\x1b[39m", self.rom.len());
-        println!("\n\x1b[33mRecompressing\x1b[36m:\x1b[39m");
-
-        for entry in entries.iter()
-        {
-            self.recompress(entry)?;
-        }
-
-        println!("\n\x1b[33mTotal savings (bytes)\x1b[36m: \x1b[32m{}\x1b[39m", self.saved_bytes);
+    // Plan fields & compress all of their entries as a single pool of parallel jobs. Failures are
+    // kept per field so that they surface in order when the plans are committed.
+    fn plan_all<S: AsRef<str> + Sync>(&self, fields: &[S]) -> Vec<Result<Plan>>
+    {
+        let plan = |field: &S| {
+            let field = field.as_ref();
+            self.plan(field).context(|| self.context(field, None, None))
+        };
+        let mut plans = parallel::map(fields, self.threads, plan);
 
-        Ok(())
+        let mut jobs = Vec::new();
+        for (p, plan) in plans.iter().enumerate()
+        {
+            let Ok(plan) = plan
+            else
+            {
+                continue;
+            };
+            let selected = |i: &usize| plan.entries[*i].is_some() && plan.selected[*i];
+            jobs.extend((0..plan.entries.len()).filter(selected).map(|i| (p, i)));
+        }
+        let attempts = parallel::map(&jobs, self.threads, |&(p, i)| match &plans[p]
+        {
+            | Ok(plan) => plan
+                .attempt(i, self.verify, self.cache.as_ref(), self.listener.as_ref())
+                .context(|| plan.context(i)),
+            | Err(_) => unreachable!(),
+        });
+
+        for ((p, i), attempt) in jobs.into_iter().zip(attempts)
+        {
+            if let Ok(plan) = &mut plans[p]
+            {
+                plan.attempts[i] = Some(attempt);
+            }
+        }
+        plans
     }
 
-    pub fn save<S: AsRef<str>>(&self, filename: S) -> Result<()>
+    #[cfg(test)]
+    pub fn recompress<S: AsRef<str>>(&mut self, json_entry: S) -> Result<()>
     {
-        self.config.save(filename)?;
-        Ok(())
+        let field = json_entry.as_ref();
+        let plan = self.plan_all(&[field]).remove(0)?;
+        self.commit(plan).context(|| self.context(field, None, None))
+    }
+
+    // Settle the compressed entries of a plan in order, then pack them into the ROM & update the
+    // definition. Verifies that dictionary streams still decode on their own given the dictionary.
+    #[rustfmt::skip]
+    fn commit(&mut self, mut plan: Plan) -> Result<()>
+    {
+        let (field, data, profile) = (plan.field.clone(), &plan.data, &plan.profile);
+        self.emit(Event::Started { name: &data.name });
+
+        let dict_mode = data.table.as_ref().is_some_and(|tbl| tbl.dict != Dict::None);
+        let (mut entries, mut codecs) = (Vec::new(), Vec::new());
+        let (mut times, mut sizes, mut costs) = (Vec::new(), Vec::new(), Vec::new());
+        let mut profiles = Vec::new();
+        let (mut isolated, mut with_dict) = (0, plan.shared.len());
+        for (i, attempt) in std::mem::take(&mut plan.attempts).into_iter().enumerate()
+        {
+            let Some(entry) = &plan.entries[i]
+            else
+            {
+                entries.push(None);
+                codecs.push(None);
+                times.push(Duration::ZERO);
+                sizes.push(Vec::new());
+                costs.push((None, false));
+                profiles.push(None);
+                continue;
+            };
+
+            // Unselected entries keep their original stream & codec. Other candidates are recorded
+            // as runners-up.
+            let (((codec, compressed), cycles, over), time, iso, runners_up, tried) = match attempt
+            {
+                | Some(attempt) =>
+                {
+                    let attempt = attempt?;
+                    let (time, iso) = (attempt.time, attempt.isolated);
+                    let mut runners_up = attempt.sizes.clone();
+                    if let Some(j) = attempt.chosen
+                    {
+                        runners_up.remove(j);
+                    }
+                    let tried = attempt.chosen.or(attempt.sizes.len().checked_sub(1));
+                    let settled = self.settle(&field, entry, (plan.compare, plan.budget), attempt);
+                    (settled.context(|| plan.context(i))?, time, iso, runners_up, tried)
+                },
+                | None =>
+                {
+                    let orig = ((entry.codec, entry.orig.clone()), None, false);
+                    (orig, Duration::ZERO, entry.orig.len(), Vec::new(), None)
+                },
+            };
+
+            // Name the aPLib profile of the stored stream, unless it fell back to another codec.
+            let profile = match tried.map(|j| &plan.codecs[j])
+            {
+                | Some(Candidate::Aplib(p)) if codec == Codec::Aplib => Some(p.name.clone()),
+                | _ => None,
+            };
+
+            // Measure against compressing the entry in isolation.
+            if dict_mode && codec == Codec::Aplib
+            {
+                if aplib::decompress_dict(&compressed, plan.dict(i))? != entry.data
+                {
+                    let e = AplibDictVerifyError(data.name.to_string(), i);
+                    return Err(e.context(plan.context(i)));
+                }
+                isolated  += iso;
+                with_dict += compressed.len();
+            }
+            entries.push(Some(compressed));
+            codecs.push(Some(codec));
+            times.push(time);
+            sizes.push(runners_up);
+            costs.push((cycles, over));
+            profiles.push(profile);
+        }
+        if dict_mode
+        {
+            let message = format!("dictionary: {} -> {} bytes", isolated, with_dict);
+            self.emit(Event::Note { message });
+        }
+        let aplib = codecs.iter().flatten().filter(|&&c| c == Codec::Aplib).count();
+        let packed = aplib > 0 && aplib == codecs.iter().flatten().count();
+
+        let (data_range, table) = match &data.table
+        {
+            | None =>       // single entry.
+            {
+                let entry       = plan.entries[0].as_ref().unwrap();
+                let compressed  = entries.remove(0).unwrap();
+                let codec       = codecs[0].unwrap();
+                let data_len    = compressed.len();
+                let bank_offset = self.place(data, data.range.start, data_len, true, |_| true)?;
+                let offset      = conv_addr(bank_offset);
+                self.dedup.insert(compressed.clone(), bank_offset);
+                let data_entry  = offset..offset + data_len;
+                self.rom.splice(data_entry, compressed);
+                self.records.push(Record {
+                    field: field.to_string(), index: None, old_addr: data.range.start,
+                    new_addr: bank_offset, old_size: entry.orig.len(), new_size: data_len,
+                    size: entry.data.len(), codec: Some(codec), profile: profiles.remove(0),
+                    dedup: None, verified: self.verify, cycles: costs[0].0,
+                    over_budget: costs[0].1, time: times[0], candidates: sizes.remove(0),
+                });
+                (bank_offset..bank_offset + data_len, None)
+            }
+            | Some(tbl) =>  // multiple entries.
+            {
+                let (old_dps, kinds) = (&plan.old_dps, &plan.kinds);
+                let init_dp = data.range.start.saturating_sub(tbl.offset); // entry 0 may be null.
+
+                // Pointers preserved into the original data keep it from being released.
+                let preserve = |(&kind, entry): (&PtrKind, &Option<Entry>)|
+                    entry.is_none() && tbl.policy(kind) == Some(PtrPolicy::Preserve);
+                let release = !kinds.iter().zip(&plan.entries).any(preserve);
+
+                // Relocate the data within reach of the table when it no longer fits. Identical
+                // entries are only counted once when they can be shared.
+                let dict_len = plan.shared.len();
+                let share    = tbl.dict == Dict::None;
+                let mut unique = HashSet::new();
+                let need = dict_len + entries.iter().flatten().filter(|d| !share || unique.insert(&d[..]))
+                                                            .map(|d| d.len()).sum::<usize>();
+                // Tables whose offset is held by code move it along with the data out of reach,
+                // unless pointers into the original data are preserved.
+                let held   = data.refs.iter().any(|r| r.ptr_offset);
+                let rebase = release && held && tbl.bank_byte.is_none();
+                let init_addr = self.place(data, tbl.offset + init_dp, need, release, |addr|
+                    tbl.can_hold(addr, need) || rebase && tbl.rebased(addr).can_hold(addr, need))?;
+                let old_offset = tbl.offset;
+                let table = match tbl.can_hold(init_addr, need)
+                {
+                    | true  => tbl.clone(),
+                    | false => tbl.rebased(init_addr),
+                };
+                let tbl     = &table;
+                let init_dp = init_addr - tbl.offset;
+
+                // Store the shared dictionary once ahead of the entries.
+                let dict_do = conv_addr(tbl.offset + init_dp);
+                self.rom.splice(dict_do..dict_do + dict_len, plan.shared.iter().copied());
+                if let Dict::Shared(_) = tbl.dict
+                {
+                    let dict_addr = tbl.offset + init_dp;
+                    self.config.update_dictionary(&field, dict_addr..dict_addr + dict_len)?;
+                }
+
+                // Pack entries after the dictionary & rebuild the pointer graph. Data that depends
+                // on a dictionary can't be shared with other entries.
+                let mut new_dp   = init_dp + dict_len;
+                let mut new_dps  = Vec::new();
+                let mut empty_dp = None;
+
+                for (i, (&kind, compressed)) in kinds.iter().zip(entries).enumerate()
+                {
+                    let codec    = codecs[i];
+                    let data_len = compressed.as_ref().map_or(0, |d| d.len());
+                    let before   = new_dp;
+                    let last_dp  = new_dps.last().copied().unwrap_or(0);
+                    let dp = match (compressed, kind, tbl.policy(kind))
+                    {
+                        | (Some(data), ..)                      => self.pack(tbl, data, (init_dp, &mut new_dp, last_dp), share),
+                        | (None, PtrKind::Alias(j), _)          => new_dps[j], // same as earlier.
+                        | (None, _, Some(PtrPolicy::Empty))     => *empty_dp.get_or_insert(new_dp),
+                        | (None, _, Some(PtrPolicy::Null))      => tbl.null_ptr.unwrap_or(0),
+                        | (None, ..)                            => old_dps[i], // preserved.
+                    };
+                    self.splice_dp(tbl, i, dp)?; // splice in data ptr.
+                    new_dps.push(dp);
+
+                    // Record the result. Data is shared when packing didn't store anything new.
+                    let dup = match codec
+                    {
+                        | Some(_) => new_dp == before,
+                        | None    => matches!(kind, PtrKind::Alias(_)),
+                    };
+                    let verified = match codec
+                    {
+                        | Some(Codec::Aplib) => self.verify || !share,
+                        | Some(_) => self.verify,
+                        | None => false,
+                    };
+                    let (old_size, size) = plan.entries[i].as_ref().map_or((0, 0), |e| (e.orig.len(), e.data.len()));
+                    self.records.push(Record {
+                        field: field.to_string(), index: Some(i), old_addr: old_offset + old_dps[i],
+                        new_addr: tbl.offset + dp, old_size, new_size: if dup { 0 } else { data_len },
+                        size, codec, profile: profiles[i].take(),
+                        dedup: dup.then_some(tbl.offset + dp), verified, cycles: costs[i].0,
+                        over_budget: costs[i].1, time: times[i],
+                        candidates: std::mem::take(&mut sizes[i]),
+                    });
+                }
+
+                // Splice in trailing pointer to the end of the packed data.
+                if tbl.end_ptr
+                {
+                    self.splice_dp(tbl, tbl.arr_len, new_dp)?;
+                }
+                (tbl.offset + init_dp..tbl.offset + new_dp, Some(table))
+            }
+        };
+
+        // Update code references to the data and pointer table.
+        self.splice_refs(data, &data_range, table.as_ref())?;
+
+        // Insert updated json entry with new data range, codec, and pointer table. The codec of a
+        // table only changes once all of its data is aPLib, as each index is detected when mixed,
+        // while a single entry takes the codec it was stored with.
+        self.config.update(&field, data_range)?;
+        match (&data.table, &plan.entries[0], codecs[0])
+        {
+            | (None, Some(entry), Some(codec)) if codec != entry.codec =>
+            {
+                self.config.update_format(&field, entry.codec.format(), codec.format())?
+            },
+            | (Some(_), ..) if packed =>
+            {
+                self.config.update_format(&field, lzss::FORMAT, aplib::FORMAT)?
+            },
+            | (Some(_), ..) if aplib > 0 =>
+            {
+                let total = codecs.iter().flatten().count();
+                let message = format!("{}/{} entries stored as aPLib, format kept", aplib, total);
+                self.emit(Event::Note { message });
+            },
+            | _ => (),
+        }
+        if let Some(tbl) = &table
+        {
+            self.config.update_table(&field, tbl)?;
+        }
+
+        self.emit(Event::Finished { name: &data.name, profile: &profile.name });
+
+        Ok(())
+    }
+
+    // Decompress a single-blob entry and expose its nested assembly children.
+    pub fn blob<S: AsRef<str>>(&self, json_entry: S) -> Result<Blob>
+    {
+        let field = json_entry.as_ref();
+        let data = self.config.extract(field)?;
+        if data.table.is_some()
+        {
+            return Err(BlobTableError(field));
+        }
+
+        // Decompress with whichever codec the entry currently uses.
+        let offset = conv_addr(data.range.start);
+        let input = self.rom.get_checked(offset..).map_err(ExtractPtrError)?;
+        let uncompressed = data.codec.unwrap_or_else(|| Codec::detect(input)).decompress(input)?;
+
+        Blob::new(field.to_string(), uncompressed, self.config.extract_assembly(field)?)
+    }
+
+    // Recompress a (possibly edited) blob and splice it back into its original range.
+    pub fn store_blob(&mut self, blob: &Blob) -> Result<()>
+    {
+        let data = self.config.extract(&blob.field)?;
+        data.check_size(0, blob.data.len())?;
+        let profile = self.profile(&data)?;
+        let compressed = aplib::compress(&blob.data, &profile)?;
+
+        // Check that the data still fits within the original range.
+        if compressed.len() > data.range.len()
+        {
+            return Err(BlobSizeError(blob.field.clone(), compressed.len(), data.range.len()));
+        }
+
+        // Splice in data.
+        let offset = conv_addr(data.range.start);
+        let data_len = compressed.len();
+        self.rom.splice(offset..offset + data_len, compressed);
+
+        // Insert updated json entry with new data range and codec.
+        let data_range = data.range.start..data.range.start + data_len;
+        self.config.update(&blob.field, data_range)?;
+        self.config.update_format(&blob.field, lzss::FORMAT, aplib::FORMAT)?;
+
+        Ok(())
+    }
+
+    // Run the in-ROM decoder on every entry of a field and compare its output with our own
+    // decompression, which uses the entry's dictionary. Aliased pointers are only run once & null
+    // or invalid ones not at all. Entries that we can't decompress are returned along with the
+    // error instead.
+    pub fn bench<S: AsRef<str>>(&self, json_entry: S) -> Result<(Vec<Bench>, Vec<Skipped>)>
+    {
+        let field = json_entry.as_ref();
+        let data = self.config.extract(field)?;
+        let decoded = match &data.table
+        {
+            | None =>
+            {
+                let asset = self.export_at(field, None, data.range.start, data.codec, &[]);
+                vec![(PtrKind::Data, asset)]
+            },
+            | Some(tbl) => self.decode_table(field, &data, tbl)?.1,
+        };
+
+        let (mut benches, mut skipped) = (Vec::new(), Vec::new());
+        for (index, (kind, asset)) in decoded.into_iter().enumerate()
+        {
+            let (asset, expected) = match (kind, asset)
+            {
+                | (PtrKind::Null | PtrKind::Invalid | PtrKind::Alias(_), _) => continue,
+                | (_, Ok((asset, _))) if asset.codec == Codec::Raw => continue, // copied as is.
+                | (_, Ok(asset)) => asset,
+                | (_, Err(e)) =>
+                {
+                    skipped.push((index, e.to_string()));
+                    continue;
+                },
+            };
+            let decoder = self.config.decoder(asset.codec.format())?;
+            let (output, cycles) = harness::run(&self.rom, &decoder, asset.addr, expected.len())?;
+            let (format, size, matches) =
+                (asset.codec.format(), expected.len(), output == expected);
+            benches.push(Bench { index, format, size, cycles, matches });
+        }
+        Ok((benches, skipped))
+    }
+
+    // Get the shared dictionary stored ahead of the entries of a table, if any.
+    fn shared_dict(&self, tbl: &PointerTable) -> Result<&[u8]>
+    {
+        let Some(range) = &tbl.dict_range
+        else
+        {
+            return Ok(&[]);
+        };
+        let offset = conv_addr(range.start);
+        self.rom.get_checked(offset..offset + range.len()).map_err(ExtractPtrError)
+    }
+
+    // Decompress the data at an address given its codec & dictionary, along with its asset
+    // description. Without a codec, it's detected from the data.
+    fn export_at(
+        &self,
+        field: &str,
+        index: Option<usize>,
+        addr: usize,
+        codec: Option<Codec>,
+        dict: &[u8],
+    ) -> Result<(Asset, Vec<u8>)>
+    {
+        let input = self.rom.get_checked(conv_addr(addr)..).map_err(ExtractPtrError)?;
+        let codec = codec.unwrap_or_else(|| Codec::detect(input));
+        let (data, size) = codec.decompress_len(input, dict)?;
+        let (field, len, hash) = (field.to_string(), data.len(), workspace::hash(&data));
+        Ok((Asset { field, index, addr, codec, size, len, hash }, data))
+    }
+
+    // Decompress every pointer of a table once with the dictionary of its entry, i.e. the shared
+    // one or the data of the preceding entry unless that one has no data, & classify the pointers
+    // as when recompressing, along with the data pointers. The codec of each index is detected, as
+    // a table mixes LZSS & aPLib data when only some of its indices were recompressed.
+    fn decode_table(
+        &self,
+        field: &str,
+        data: &ExtractedData,
+        tbl: &PointerTable,
+    ) -> Result<(Vec<usize>, Vec<Decoded>)>
+    {
+        let mut bank = TblBank::default();
+        let mut dps = Vec::new();
+        for i in 0..tbl.arr_len
+        {
+            dps.push(self.extract_dp(tbl, i, &mut bank)?);
+        }
+        let start = data.range.start.saturating_sub(tbl.offset);
+        let end = data.range.end.saturating_sub(tbl.offset);
+
+        let shared = self.shared_dict(tbl)?;
+        let mut decoded: Vec<Result<(Asset, Vec<u8>)>> = Vec::new();
+        for (i, &dp) in dps.iter().enumerate()
+        {
+            let has_data = |dp: usize| Some(dp) != tbl.null_ptr && (start..end).contains(&dp);
+            let previous = match i.checked_sub(1).map(|j| (dps[j], &decoded[j]))
+            {
+                | Some((dp, Ok((_, data)))) if has_data(dp) => &data[..],
+                | _ => &[],
+            };
+            let dict = match tbl.dict
+            {
+                | Dict::None => &[],
+                | Dict::Shared(_) => shared,
+                | Dict::Previous => previous,
+            };
+            let asset = self.export_at(field, Some(i), tbl.offset + dp, None, dict);
+            decoded.push(asset);
+        }
+        let lens: Vec<_> = decoded.iter().map(|a| a.as_ref().map_or(0, |(a, _)| a.size)).collect();
+        let kinds = pointers::classify(&dps, &lens, start..end, tbl.null_ptr);
+        Ok((dps, kinds.into_iter().zip(decoded).collect()))
+    }
+
+    // Get the original compressed stream of an exported asset.
+    pub fn stream(&self, asset: &Asset) -> &[u8]
+    {
+        let offset = conv_addr(asset.addr);
+        self.rom.get(offset..offset + asset.size).unwrap_or_default()
+    }
+
+    // Decompress every compressed entry of the definition, one asset per entry or table index.
+    // Null and invalid table pointers are skipped, as are interior ones that don't decode.
+    pub fn export(&self) -> Result<Vec<(Asset, Vec<u8>)>>
+    {
+        let mut assets = Vec::new();
+        for field in self.config.compressed()
+        {
+            let data = self.config.extract(&field)?;
+            let Some(tbl) = &data.table
+            else
+            {
+                assets.push(self.export_at(&field, None, data.range.start, data.codec, &[])?);
+                continue;
+            };
+
+            // Skip pointers without data of their own as when recompressing.
+            for (kind, asset) in self.decode_table(&field, &data, tbl)?.1
+            {
+                match kind
+                {
+                    | PtrKind::Data | PtrKind::Alias(_) => assets.push(asset?),
+                    | PtrKind::Interior(_) => assets.extend(asset.ok()),
+                    | PtrKind::Null | PtrKind::Invalid => (),
+                }
+            }
+        }
+        Ok(assets)
+    }
+
+    // Report of every entry recompressed so far.
+    pub fn report(&self) -> Report
+    {
+        #[rustfmt::skip]
+        let report = Report {
+            records: self.records.clone(), failures: self.failures.clone(),
+            saved_bytes: self.saved_bytes,
+        };
+        report
+    }
+
+    // Map of the old & new addresses of every entry in a report, labelled with their names.
+    pub fn relocation_map(&self, report: &Report) -> Map
+    {
+        Map::new(report, |field| self.config.extract(field).map_or(field.to_string(), |d| d.name))
+    }
+
+    // Run recompression on a scratch copy whose writes are discarded afterwards, leaving the ROM
+    // and definition untouched. Returns the projected layout & savings, e.g. of 'process'.
+    pub fn dry_run<F: FnOnce(&mut Rom) -> Result<Report>>(&self, run: F) -> Result<Report>
+    {
+        self.emit(Event::DryRun);
+        run(&mut self.clone())
+    }
+
+    pub fn process(&mut self) -> Result<Report>
+    {
+        let fields = self.selector.fields(&ENTRIES);
+        self.emit(Event::Begin { rom_size: self.rom.len(), entries: fields.len() });
+        self.recompress_all(&fields)?;
+
+        let report = self.report();
+        self.emit(Event::Summary(&report));
+        Ok(report)
+    }
+
+    // Recompress entries as a single transaction, so that the ROM & definition are rolled back
+    // together if any entry fails. With 'keep_going', every entry is a transaction of its own
+    // instead and failing ones are skipped & reported at the end.
+    fn recompress_all<S: AsRef<str> + Sync>(&mut self, fields: &[S]) -> Result<()>
+    {
+        // Entries don't overlap, so all of them can be planned & compressed up front.
+        let plans = self.plan_all(fields);
+
+        // Keep the cache within its limit now that every stream of this run is stored.
+        if let Some(cache) = &self.cache
+        {
+            cache.evict()?;
+        }
+
+        let snapshot = self.clone();
+        for (field, plan) in fields.iter().zip(plans)
+        {
+            let field = field.as_ref();
+            let entry_snapshot = self.keep_going.then(|| self.clone());
+            let commit =
+                |rom: &mut Rom, plan| rom.commit(plan).context(|| rom.context(field, None, None));
+            let planned = plan.is_ok();
+            if let Err(e) = plan.and_then(|plan| commit(self, plan))
+            {
+                // Entries that failed planning weren't started.
+                if !planned
+                {
+                    let name = self.context(field, None, None).name;
+                    self.emit(Event::Started { name: name.as_deref().unwrap_or(field) });
+                }
+                self.emit(Event::Failed { field, error: &e });
+                match entry_snapshot
+                {
+                    | Some(entry_snapshot) => *self = entry_snapshot,
+                    | None =>
+                    {
+                        *self = snapshot;
+                        return Err(e);
+                    },
+                }
+                self.failures.push((field.to_string(), e.to_string()));
+            }
+        }
+        Ok(())
+    }
+
+    // Load the edited assets of a workspace, skipping files whose content matches the manifest.
+    // Returns the fields with any edited asset in manifest order.
+    pub fn import<P: AsRef<Path>>(&mut self, dir: P) -> Result<Vec<String>>
+    {
+        let dir = dir.as_ref();
+        let mut fields = Vec::new();
+        for asset in Manifest::load(dir)?.assets
+        {
+            let data = fs::read(dir.join(asset.file()))?;
+            if data.len() == asset.len && workspace::hash(&data) == asset.hash
+            {
+                continue; // unchanged.
+            }
+            if !fields.contains(&asset.field)
+            {
+                fields.push(asset.field.clone());
+            }
+            self.edits.insert((asset.field, asset.index), data);
+        }
+        Ok(fields)
+    }
+
+    // Recompress every entry edited in a workspace, leaving the others untouched. Only the edited
+    // indices of a table are recompressed, unless others were selected.
+    pub fn rebuild<P: AsRef<Path>>(&mut self, dir: P) -> Result<Report>
+    {
+        let fields = self.import(dir)?;
+        if self.selector == Selector::default()
+        {
+            self.selector = Selector::none();
+        }
+        self.emit(Event::Begin { rom_size: self.rom.len(), entries: fields.len() });
+        self.recompress_all(&fields)?;
+
+        let report = self.report();
+        self.emit(Event::Summary(&report));
+        Ok(report)
+    }
+
+    pub fn save<S: AsRef<str>>(&self, filename: S) -> Result<()>
+    {
+        self.config.save(filename)?;
+        Ok(())
+    }
+
+    pub fn save_rom<S: AsRef<str>>(&self, filename: S) -> Result<()>
+    {
+        fs::write(filename.as_ref().to_string() + ".sfc", &self.rom)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use std::sync::{Arc, Mutex};
+
+    use super::{conv_addr, Rom, TblBank};
+    use crate::{
+        aplib::{self, Profile},
+        codec::Codec,
+        events::Event,
+        harness::Bench,
+        json::{Config, PointerTable, Reference},
+        lzss,
+        pointers::PtrKind,
+        report::Report,
+        selector::Selector,
+        workspace,
+    };
+
+    fn rom(bytes: Vec<u8>) -> Rom
+    {
+        Rom::with_config(bytes, Config::new("{}").unwrap())
+    }
+
+    // ROM described by the test definition.
+    fn fixture(test: &str, bytes: Vec<u8>) -> Rom
+    {
+        Rom::with_config(bytes, Config::new(test).unwrap())
+    }
+
+    // LZSS stream of ten copies of the byte, as the game stores it.
+    fn stream(byte: u8) -> [u8; 6]
+    {
+        [0x06, 0x00, 0x01, byte, 0xDE, 0x37]
+    }
+
+    // 0x80 bytes starting with the head (e.g. a pointer table), with a stream at each offset.
+    fn image(head: &[u8], streams: &[(usize, u8)]) -> Vec<u8>
+    {
+        let mut bytes = head.to_vec();
+        bytes.resize(0x80, 0);
+        for &(offset, byte) in streams
+        {
+            bytes[offset..offset + 6].copy_from_slice(&stream(byte));
+        }
+        bytes
+    }
+
+    #[test]
+    fn blob()
+    {
+        let test = r##"
+    {
+        "assembly": {
+            "blob": {
+                "name": "Blob",
+                "range": "0xC00000-0xC00020",
+                "format": "ff6-lzss",
+                "isSequential": true,
+                "assembly": {
+                    "a": { "range": "0x0000-0x0004" },
+                    "b": { "range": "0x0004-0x000A" }
+                }
+            }
+        }
+    }"##;
+        let mut rom = fixture(test, image(&[], &[(0x00, 0x11)]));
+
+        // Replace a child and store the blob.
+        let mut blob = rom.blob("blob").unwrap();
+        assert_eq!(blob.child("a").unwrap(), [0x11; 4]);
+        blob.replace("b", &[0x22; 6]).unwrap();
+        rom.store_blob(&blob).unwrap();
+
+        // Reload the recompressed blob.
+        let blob = rom.blob("blob").unwrap();
+        assert_eq!(blob.child("a").unwrap(), [0x11; 4]);
+        assert_eq!(blob.child("b").unwrap(), [0x22; 6]);
+        assert_eq!(rom.config.json()["assembly"]["blob"]["format"], "ff6-aplib");
+    }
+
+    #[test]
+    fn blob_table_error()
+    {
+        let test = r##"
+    {
+        "assembly": {
+            "blob": {
+                "name": "Blob",
+                "range": "0xC00000-0xC00020",
+                "arrayLength": 1,
+                "pointerTable": { "range": "0xC00020-0xC00022" }
+            }
+        }
+    }"##;
+        let rom = fixture(test, vec![]);
+        let err = rom.blob("blob").unwrap_err();
+        assert_eq!(err.to_string(), "Blob Error: `blob` is an array with a pointer table");
+    }
+
+    #[test]
+    fn recompress_dedup()
+    {
+        let test = r##"
+    {
+        "assembly": {
+            "a": {
+                "name": "A",
+                "range": "0xC00010-0xC00022",
+                "arrayLength": 3,
+                "pointerTable": { "range": "0xC00000-0xC00006", "offset": "0xC00000" }
+            },
+            "b": {
+                "name": "B",
+                "range": "0xC00030-0xC00040",
+                "arrayLength": 1,
+                "pointerTable": { "range": "0xC00006-0xC00008", "offset": "0xC00000" }
+            }
+        }
+    }"##;
+        // Entry a[1] is the same as a[0], and b[0] the same as a[2].
+        let head = [0x10, 0x00, 0x16, 0x00, 0x1C, 0x00, 0x30, 0x00];
+        let streams = [(0x10, 0x11), (0x16, 0x11), (0x1C, 0x22), (0x30, 0x22)];
+        let mut rom = fixture(test, image(&head, &streams));
+        rom.recompress("a").unwrap();
+        rom.recompress("b").unwrap();
+
+        // Data is only shared within a table, so b keeps its own copy in its range.
+        let len = aplib::compress(&[0x11; 10], &Profile::default()).unwrap().len();
+        let mut bank = TblBank::default();
+        let a = rom.config.extract("a").unwrap().table.unwrap();
+        let b = rom.config.extract("b").unwrap().table.unwrap();
+        assert_eq!(rom.extract_dp(&a, 0, &mut bank).unwrap(), 0x10);
+        assert_eq!(rom.extract_dp(&a, 1, &mut bank).unwrap(), 0x10);
+        assert_eq!(rom.extract_dp(&a, 2, &mut bank).unwrap(), 0x10 + len);
+        assert_eq!(rom.extract_dp(&b, 0, &mut bank).unwrap(), 0x30);
+        assert_eq!(rom.records[3].dedup, None);
+    }
+
+    #[test]
+    fn recompress_dedup_twice()
+    {
+        let test = r##"
+    {
+        "assembly": {
+            "a": {
+                "name": "A",
+                "range": "0xC00010-0xC00022",
+                "arrayLength": 3,
+                "format": "ff6-lzss",
+                "pointerTable": { "range": "0xC00000-0xC00006", "offset": "0xC00000" }
+            },
+            "b": {
+                "name": "B",
+                "range": "0xC00030-0xC00040",
+                "arrayLength": 1,
+                "format": "ff6-lzss",
+                "pointerTable": { "range": "0xC00006-0xC00008", "offset": "0xC00000" }
+            }
+        }
+    }"##;
+        // Shared data must still be classified as data by the next run over the tables.
+        let head = [0x10, 0x00, 0x16, 0x00, 0x1C, 0x00, 0x30, 0x00];
+        let streams = [(0x10, 0x11), (0x16, 0x11), (0x1C, 0x22), (0x30, 0x22)];
+        let mut rom = fixture(test, image(&head, &streams));
+        for _ in 0..2
+        {
+            rom.recompress("a").unwrap();
+            rom.recompress("b").unwrap();
+        }
+
+        let kinds: Vec<_> = rom.records[4..].iter().map(|r| (r.index, r.new_size == 0)).collect();
+        assert_eq!(kinds, [(Some(0), false), (Some(1), true), (Some(2), false), (Some(0), false)]);
+        let first: Vec<_> = rom.export().unwrap().iter().map(|(_, data)| data[0]).collect();
+        assert_eq!(first, [0x11, 0x11, 0x22, 0x22]);
+    }
+
+    #[test]
+    fn report()
+    {
+        let test = r##"
+    {
+        "assembly": {
+            "a": {
+                "name": "A",
+                "range": "0xC00010-0xC00022",
+                "arrayLength": 3,
+                "pointerTable": { "range": "0xC00000-0xC00006", "offset": "0xC00000" }
+            },
+            "b": { "name": "B", "range": "0xC00030-0xC00040" }
+        }
+    }"##;
+        // Entry 1 is the same as 0, and entry 2 aliases it.
+        let head = [0x10, 0x00, 0x16, 0x00, 0x10, 0x00];
+        let mut rom = fixture(test, image(&head, &[(0x10, 0x11), (0x16, 0x11), (0x30, 0x22)]));
+        rom.set_verify(true);
+        rom.recompress("a").unwrap();
+        rom.recompress("b").unwrap();
+
+        let len = aplib::compress(&[0x11; 10], &Profile::default()).unwrap().len();
+        let report = rom.report();
+        let records: Vec<_> = report
+            .records
+            .iter()
+            .map(|r| (r.index, r.old_addr, r.new_addr, r.old_size, r.new_size, r.dedup))
+            .collect();
+        assert_eq!(
+            records,
+            [
+                (Some(0), 0xC00010, 0xC00010, 6, len, None),
+                (Some(1), 0xC00016, 0xC00010, 6, 0, Some(0xC00010)), // identical data.
+                (Some(2), 0xC00010, 0xC00010, 0, 0, Some(0xC00010)), // aliased pointer.
+                (None, 0xC00030, 0xC00030, 6, len, None),
+            ]
+        );
+        assert_eq!(report.records[0].codec, Some(Codec::Aplib));
+        assert_eq!(report.records[0].profile.as_deref(), Some("default"));
+        assert_eq!(report.records[2].codec, None);
+        assert_eq!(
+            report.records.iter().map(|r| r.verified).collect::<Vec<_>>(),
+            [true, true, false, true]
+        );
+
+        // The relocation map labels every index with the entry's name.
+        let map = rom.relocation_map(&report);
+        let names: Vec<_> = map.relocations.iter().map(|r| (r.name.as_str(), r.index)).collect();
+        assert_eq!(names, [("A", Some(0)), ("A", Some(1)), ("A", Some(2)), ("B", None)]);
+    }
+
+    #[test]
+    fn dry_run()
+    {
+        let test = r##"
+    {
+        "freeSpace": ["0xC00040-0xC00080"],
+        "assembly": {
+            "a": { "name": "A", "range": "0xC00010-0xC00016", "canRelocate": true },
+            "b": { "name": "B", "range": "0xC00020-0xC00030" }
+        }
+    }"##;
+        let bytes = image(&[], &[(0x10, 0x11), (0x20, 0x22)]);
+        let mut rom = fixture(test, bytes.clone());
+        let run = |rom: &mut Rom| -> crate::result::Result<_> {
+            rom.recompress("a")?;
+            rom.recompress("b")?;
+            Ok(rom.report())
+        };
+        let projected = rom.dry_run(run).unwrap();
+
+        // Nothing was written, and the real run matches the projection.
+        assert_eq!(rom.rom, bytes);
+        assert_eq!(rom.config.json(), Config::new(test).unwrap().json());
+        assert_eq!(rom.report().records.len(), 0);
+        let report = run(&mut rom).unwrap();
+        let layout = |r: &Report| -> Vec<_> {
+            r.records.iter().map(|r| (r.new_addr, r.new_size, r.codec)).collect()
+        };
+        assert_eq!(layout(&projected), layout(&report));
+        assert_eq!(projected.saved_bytes, report.saved_bytes);
+        assert_ne!(rom.rom, bytes);
+    }
+
+    #[test]
+    fn recompress_all_rollback()
+    {
+        let test = r##"
+    {
+        "assembly": {
+            "a": { "name": "A", "range": "0xC00010-0xC00020" },
+            "b": { "name": "B", "range": "0xC00020-0xC00030" },
+            "c": { "name": "C", "range": "0xC00030-0xC00040" }
+        }
+    }"##;
+        let bytes = image(&[], &[(0x10, 0x11), (0x30, 0x22)]); // b has no data.
+
+        // Every entry is rolled back when one fails.
+        let mut rom = fixture(test, bytes.clone());
+        let err = rom.recompress_all(&["a", "b", "c"]).unwrap_err();
+        let msg = "`b` (B) at 0xC00020 (ROM offset 0x000020): LZSS Decompression Error: Invalid \
+                   compression length of 0";
+        assert_eq!(err.to_string(), msg);
+        assert_eq!(rom.rom, bytes);
+        assert_eq!(rom.config.json(), Config::new(test).unwrap().json());
+        assert_eq!(rom.report().records.len(), 0);
+
+        // Only the failing entry is skipped when keeping going.
+        let mut rom = fixture(test, bytes.clone());
+        rom.set_keep_going(true);
+        rom.recompress_all(&["a", "b", "c"]).unwrap();
+        let report = rom.report();
+        let fields: Vec<_> = report.records.iter().map(|r| &r.field[..]).collect();
+        assert_eq!(fields, ["a", "c"]);
+        let failures = [("b".into(), msg.into())];
+        assert_eq!(report.failures, failures);
+        assert_eq!(report.records[0].profile.as_deref(), Some("default"));
+        assert_eq!(rom.rom[0x20..0x26], bytes[0x20..0x26]);
+    }
+
+    #[test]
+    fn events()
+    {
+        let test = r##"
+    {
+        "assembly": {
+            "a": { "name": "A", "range": "0xC00010-0xC00020" },
+            "b": { "name": "B", "range": "0xC00020-0xC00030" }
+        }
+    }"##;
+        let mut rom = fixture(test, image(&[], &[(0x10, 0x11)])); // b has no data.
+        rom.set_keep_going(true);
+
+        // Collect every event but progress, which depends on apultra.
+        let events = Arc::new(Mutex::new(Vec::new()));
+        let log = events.clone();
+        rom.set_listener(Some(Arc::new(move |event: &Event| {
+            let event = match event
+            {
+                | Event::Started { name } => format!("started {}", name),
+                | Event::Warning { field, .. } => format!("warning {}", field),
+                | Event::Finished { name, .. } => format!("finished {}", name),
+                | Event::Failed { field, .. } => format!("failed {}", field),
+                | _ => return,
+            };
+            log.lock().unwrap().push(event);
+        })));
+        rom.recompress_all(&["a", "b"]).unwrap();
+
+        let mut expected = vec!["started A", "finished A"];
+        if aplib::compress(&[0x11; 10], &Profile::default()).unwrap().len() > 6
+        {
+            expected.insert(1, "warning a");
+        }
+        expected.extend(["started B", "failed b"]);
+        assert_eq!(*events.lock().unwrap(), expected);
+    }
+
+    #[test]
+    fn recompress_all_threads()
+    {
+        let test = r##"
+    {
+        "assembly": {
+            "a": {
+                "name": "A",
+                "range": "0xC00010-0xC00022",
+                "arrayLength": 4,
+                "pointerTable": {
+                    "range": "0xC00000-0xC00008",
+                    "offset": "0xC00000"
+                }
+            },
+            "b": { "name": "B", "range": "0xC00030-0xC00040" }
+        }
+    }"##;
+        // Entry 3 is identical to entry 0, which entry 2 aliases.
+        let head = [0x10, 0x00, 0x16, 0x00, 0x10, 0x00, 0x1C, 0x00];
+        let bytes = image(&head, &[(0x10, 0x11), (0x16, 0x22), (0x1C, 0x11), (0x30, 0x33)]);
+
+        // Compressing on several threads gives the same output as a single thread.
+        let run = |threads| {
+            let mut rom = fixture(test, bytes.clone());
+            rom.set_threads(threads);
+            rom.recompress_all(&["a", "b"]).unwrap();
+            let layout: Vec<_> =
+                rom.records.iter().map(|r| (r.new_addr, r.new_size, r.dedup)).collect();
+            (rom.rom, rom.config.json().clone(), rom.saved_bytes, layout)
+        };
+        let serial = run(1);
+        assert_eq!(run(4), serial);
+        assert_eq!(serial.3.len(), 5);
+        assert_eq!(serial.3[2].2, Some(0xC00010));
+    }
+
+    #[test]
+    fn recompress_selection()
+    {
+        let test = r##"
+    {
+        "assembly": {
+            "a": {
+                "name": "A",
+                "range": "0xC00010-0xC00030",
+                "arrayLength": 3,
+                "pointerTable": {
+                    "range": "0xC00000-0xC00006",
+                    "offset": "0xC00000"
+                }
+            },
+            "b": { "name": "B", "range": "0xC00030-0xC00036" }
+        }
+    }"##;
+        let head = [0x10, 0x00, 0x16, 0x00, 0x1C, 0x00];
+        let bytes = image(&head, &[(0x10, 0x11), (0x16, 0x22), (0x1C, 0x33), (0x30, 0x44)]);
+        let mut rom = fixture(test, bytes.clone());
+        rom.set_selector(Selector::new("a[1],!b").unwrap());
+        rom.recompress_all(&rom.selector.fields(&["a", "b"])).unwrap();
+
+        // Only the selected index is recompressed, the others are repacked as they were.
+        let codecs: Vec<_> = rom.records.iter().map(|r| (&r.field[..], r.codec)).collect();
+        let (lzss, aplib) = (Some(Codec::Lzss), Some(Codec::Aplib));
+        assert_eq!(codecs, [("a", lzss), ("a", aplib), ("a", lzss)]);
+        let len = aplib::compress(&[0x22; 10], &Profile::default()).unwrap().len();
+        let mut bank = TblBank::default();
+        let a = rom.config.extract("a").unwrap().table.unwrap();
+        assert_eq!(rom.extract_dp(&a, 0, &mut bank).unwrap(), 0x10);
+        assert_eq!(rom.extract_dp(&a, 1, &mut bank).unwrap(), 0x16);
+        assert_eq!(rom.extract_dp(&a, 2, &mut bank).unwrap(), 0x16 + len);
+        assert_eq!(rom.rom[0x16 + len..0x16 + len + 6], bytes[0x1C..0x22]);
+        assert_eq!(rom.rom[0x30..0x36], bytes[0x30..0x36]);
+    }
+
+    #[test]
+    fn recompress_selection_passes()
+    {
+        let test = r##"
+    {
+        "assembly": {
+            "a": {
+                "name": "A",
+                "range": "0xC00010-0xC00040",
+                "arrayLength": 3,
+                "format": "ff6-lzss",
+                "pointerTable": { "range": "0xC00000-0xC00006", "offset": "0xC00000" }
+            }
+        }
+    }"##;
+        let head = [0x10, 0x00, 0x16, 0x00, 0x1C, 0x00];
+        let mut rom = fixture(test, image(&head, &[(0x10, 0x11), (0x16, 0x22), (0x1C, 0x33)]));
+        rom.set_selector(Selector::new("a[0..2]").unwrap());
+        rom.recompress("a").unwrap();
+        assert_eq!(rom.config.extract("a").unwrap().codec, Some(Codec::Lzss)); // mixed codecs.
+        rom.set_selector(Selector::new("a[2..3]").unwrap());
+        rom.set_codecs(vec!["ff6-aplib".into(), "ff6-lzss".into()]);
+        rom.recompress("a").unwrap();
+
+        // The second pass keeps the aPLib data of the first one as it is.
+        let codecs: Vec<_> = rom.records[3..].iter().map(|r| r.codec).collect();
+        let (lzss, aplib) = (Some(Codec::Lzss), Some(Codec::Aplib));
+        let len = aplib::compress(&[0x11; 10], &Profile::default()).unwrap().len();
+        assert_eq!(codecs, [aplib, aplib, if len < 6 { aplib } else { lzss }]);
+        assert_eq!(rom.config.extract("a").unwrap().codec, codecs[2]);
+        let sizes: Vec<_> = rom.records[3..].iter().map(|r| (r.old_size, r.new_size)).collect();
+        assert_eq!(sizes, [(len, len), (len, len), (6, len.min(6))]);
+        let assets = rom.export().unwrap();
+        let data: Vec<_> = assets.iter().map(|(_, data)| data[0]).collect();
+        assert_eq!(data, [0x11, 0x22, 0x33]);
+    }
+
+    #[test]
+    fn recompress_pointer_graph()
+    {
+        let test = r##"
+    {
+        "assembly": {
+            "a": {
+                "name": "A",
+                "range": "0xC00010-0xC00020",
+                "arrayLength": 5,
+                "pointerTable": {
+                    "range": "0xC00000-0xC0000A",
+                    "offset": "0xC00000",
+                    "nullPointer": "0xFFFF",
+                    "invalidPointer": "preserve"
+                }
+            }
+        }
+    }"##;
+        // Entry 1 aliases entry 0, entry 2 is null & entry 3 points outside the range.
+        let head = [0x10, 0x00, 0x10, 0x00, 0xFF, 0xFF, 0x40, 0x00, 0x16, 0x00];
+        let mut rom = fixture(test, image(&head, &[(0x10, 0x11), (0x16, 0x22)]));
+        rom.recompress("a").unwrap();
+
+        let len = aplib::compress(&[0x11; 10], &Profile::default()).unwrap().len();
+        let mut bank = TblBank::default();
+        let a = rom.config.extract("a").unwrap().table.unwrap();
+        assert_eq!(rom.extract_dp(&a, 0, &mut bank).unwrap(), 0x10);
+        assert_eq!(rom.extract_dp(&a, 1, &mut bank).unwrap(), 0x10);
+        assert_eq!(rom.extract_dp(&a, 2, &mut bank).unwrap(), 0xFFFF);
+        assert_eq!(rom.extract_dp(&a, 3, &mut bank).unwrap(), 0x40);
+        assert_eq!(rom.extract_dp(&a, 4, &mut bank).unwrap(), 0x10 + len);
+    }
+
+    #[test]
+    fn recompress_null_first()
+    {
+        let test = r##"
+    {
+        "assembly": {
+            "a": {
+                "name": "A",
+                "range": "0xC00010-0xC00020",
+                "arrayLength": 2,
+                "format": "ff6-lzss",
+                "pointerTable": {
+                    "range": "0xC00000-0xC00004",
+                    "offset": "0xC00000",
+                    "nullPointer": "0xFFFF"
+                }
+            }
+        }
+    }"##;
+        let mut rom = fixture(test, image(&[0xFF, 0xFF, 0x10, 0x00], &[(0x10, 0x11)]));
+        rom.recompress("a").unwrap();
+
+        // Entry 0 stays null & the data is packed from the start of the range.
+        let mut bank = TblBank::default();
+        let a = rom.config.extract("a").unwrap().table.unwrap();
+        assert_eq!(rom.extract_dp(&a, 0, &mut bank).unwrap(), 0xFFFF);
+        assert_eq!(rom.extract_dp(&a, 1, &mut bank).unwrap(), 0x10);
+        assert_eq!(rom.report().records[1].codec, Some(Codec::Aplib));
+        let assets = rom.export().unwrap();
+        assert_eq!(assets.iter().map(|(a, _)| a.index).collect::<Vec<_>>(), [Some(1)]);
+    }
+
+    #[test]
+    fn decode_table_aplib()
+    {
+        let test = r##"
+    {
+        "assembly": {
+            "a": {
+                "name": "A",
+                "range": "0xC00010-0xC00020",
+                "arrayLength": 3,
+                "format": "ff6-aplib",
+                "pointerTable": { "range": "0xC00000-0xC00006", "offset": "0xC00000" }
+            }
+        }
+    }"##;
+        // Pointers are classified by the size of the aPLib streams, which have no length header.
+        let mut bytes = image(&[0x10, 0x00, 0x18, 0x00, 0x14, 0x00], &[]);
+        let aplib = [0xFF, 0xFF, 0, 173, 1, 86, 192, 0];
+        bytes[0x10..0x18].copy_from_slice(&aplib);
+        bytes[0x18..0x20].copy_from_slice(&aplib);
+        let rom = fixture(test, bytes);
+        let data = rom.config.extract("a").unwrap();
+        let (dps, decoded) = rom.decode_table("a", &data, data.table.as_ref().unwrap()).unwrap();
+        assert_eq!(dps, [0x10, 0x18, 0x14]);
+        let kinds: Vec<_> = decoded.iter().map(|(kind, _)| *kind).collect();
+        assert_eq!(kinds, [PtrKind::Data, PtrKind::Data, PtrKind::Interior(0)]);
+    }
+
+    #[test]
+    fn recompress_mixed_codecs()
+    {
+        let test = r##"
+    {
+        "assembly": {
+            "a": {
+                "name": "A",
+                "range": "0xC00010-0xC00030",
+                "arrayLength": 2,
+                "format": "ff6-lzss",
+                "codecs": ["ff6-lzss"],
+                "pointerTable": { "range": "0xC00000-0xC00004", "offset": "0xC00000" }
+            },
+            "b": { "name": "B", "range": "0xC00040-0xC00050", "format": "ff6-aplib" }
+        }
+    }"##;
+        // Entry 0 & b were recompressed to aPLib by an earlier run, while entry 1 is still LZSS.
+        let aplib = [0xFF, 0xFF, 0, 173, 1, 86, 192, 0];
+        let mut bytes = image(&[0x10, 0x00, 0x18, 0x00], &[(0x18, 0x11)]);
+        bytes[0x10..0x18].copy_from_slice(&aplib);
+        bytes[0x40..0x48].copy_from_slice(&aplib);
+        let mut rom = fixture(test, bytes);
+        rom.recompress("a").unwrap();
+        rom.recompress("b").unwrap();
+
+        // Every stream is decoded with its own codec.
+        let records = rom.report().records;
+        let sizes: Vec<_> = records.iter().map(|r| (r.old_size, r.size)).collect();
+        assert_eq!(sizes, [(8, 100), (6, 10), (8, 100)]);
+        let assets = rom.export().unwrap();
+        assert_eq!(assets[0].1, [0; 100]);
+        assert_eq!(assets[1].1, [0x11; 10]);
+    }
+
+    #[test]
+    fn recompress_pointer_policy_error()
+    {
+        let test = r##"
+    {
+        "assembly": {
+            "a": {
+                "name": "A",
+                "range": "0xC00010-0xC00016",
+                "arrayLength": 2,
+                "pointerTable": {
+                    "range": "0xC00000-0xC00004",
+                    "offset": "0xC00000",
+                    "interiorPointer": "error"
+                }
+            }
+        }
+    }"##;
+        let bytes = image(&[0x10, 0x00, 0x12, 0x00], &[(0x10, 0x11)]);
+        let mut rom = fixture(test, bytes.clone());
+        let err = rom.recompress("a").unwrap_err();
+        let msg =
+            "`a` (A) entry 1 at 0xC00012 (ROM offset 0x000012): Pointer Error: `A` entry 1 is \
+                   an interior pointer";
+        assert_eq!(err.to_string(), msg);
+        assert_eq!(rom.rom, bytes);
+    }
+
+    #[test]
+    fn recompress_shared_dictionary()
+    {
+        let test = r##"
+    {
+        "assembly": {
+            "a": {
+                "name": "A",
+                "range": "0xC00010-0xC00030",
+                "arrayLength": 2,
+                "pointerTable": {
+                    "range": "0xC00000-0xC00004",
+                    "offset": "0xC00000",
+                    "dictionary": "shared",
+                    "dictionarySize": "0x4"
+                }
+            }
+        }
+    }"##;
+        let head = [0x10, 0x00, 0x16, 0x00];
+        let mut rom = fixture(test, image(&head, &[(0x10, 0x11), (0x16, 0x11)]));
+        rom.recompress("a").unwrap();
+
+        // The dictionary is stored ahead of the entries, which are no longer shared.
+        let j_table = &rom.config.json()["assembly"]["a"]["pointerTable"];
+        assert_eq!(j_table["dictionaryRange"], "0xC00010-0xC00014");
+        assert_eq!(rom.rom[0x10..0x14], [0x11; 4]);
+        let mut bank = TblBank::default();
+        let a = rom.config.extract("a").unwrap().table.unwrap();
+        let dp0 = rom.extract_dp(&a, 0, &mut bank).unwrap();
+        let dp1 = rom.extract_dp(&a, 1, &mut bank).unwrap();
+        assert_eq!(dp0, 0x14);
+        assert!(dp1 > dp0);
+        let data = aplib::decompress_dict(&rom.rom[dp1..], &rom.rom[0x10..0x14]).unwrap();
+        assert_eq!(data, [0x11; 10]);
+    }
+
+    #[test]
+    fn recompress_profile()
+    {
+        let test = r##"
+    {
+        "assembly": {
+            "a": { "name": "A", "range": "0xC00000-0xC00010", "compressionProfile": "near" },
+            "b": { "name": "B", "range": "0xC00010-0xC00020" }
+        }
+    }"##;
+        let mut rom = fixture(test, image(&[], &[(0x00, 0x11), (0x10, 0x22)]));
+        rom.set_profile("fast");
+        rom.recompress("a").unwrap();
+        rom.recompress("b").unwrap();
+        rom.set_profile("default");
+        rom.recompress("b").unwrap();
+
+        // The profile is only reported, so the global one still applies to the next run.
+        let profiles: Vec<_> = rom.records.iter().map(|r| r.profile.as_deref()).collect();
+        assert_eq!(profiles, [Some("near"), Some("fast"), Some("default")]);
+        assert_eq!(rom.config.json()["assembly"]["b"].get("compressionProfile"), None);
+
+        let test = r##"{ "assembly": { "a": { "name": "A", "range": "0xC00000-0xC00006" } } }"##;
+        let mut rom = fixture(test, vec![0; 0x10]);
+        rom.set_profile("other");
+        let err = rom.recompress("a").unwrap_err();
+        assert_eq!(err.to_string(), "`a` (A): Profile Error: unknown compression profile `other`");
+    }
+
+    #[test]
+    fn recompress_size_error()
+    {
+        let test = r##"
+    {
+        "assembly": {
+            "a": {
+                "name": "A",
+                "range": "0xC00000-0xC00010",
+                "maxSize": "0x0A",
+                "assembly": {}
+            },
+            "b": {
+                "name": "B",
+                "range": "0xC00010-0xC00016",
+                "type": "tilemap",
+                "format": "snes4bppTile",
+                "width": 2,
+                "height": 2
+            }
+        }
+    }"##;
+        let mut rom = fixture(test, image(&[], &[(0x00, 0x11), (0x10, 0x22)]));
+        rom.recompress("a").unwrap();
+        let err = rom.recompress("b").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "`b` (B) at 0xC00010 (ROM offset 0x000010): Size Error: `B` entry 0 decompresses to 10 \
+             bytes, exceeding its buffer (8)"
+        );
+
+        // Edited data is checked before it is stored.
+        let mut blob = rom.blob("a").unwrap();
+        blob.data.push(0);
+        let err = rom.store_blob(&blob).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Size Error: `A` entry 0 decompresses to 11 bytes, exceeding its buffer (10)"
+        );
+    }
+
+    #[test]
+    fn recompress_budget()
+    {
+        let test = r##"
+    {
+        "assembly": {
+            "a": { "name": "A", "range": "0xC00000-0xC00010", "format": "ff6-lzss" },
+            "b": {
+                "name": "B",
+                "range": "0xC00010-0xC00020",
+                "format": "ff6-lzss",
+                "maxDecodeCycles": 1
+            }
+        }
+    }"##;
+        let bytes = image(&[], &[(0x00, 0x11), (0x10, 0x22)]);
+        let mut rom = fixture(test, bytes.clone());
+        rom.set_budget(Some(1_000_000));
+        rom.recompress("a").unwrap();
+        rom.recompress("b").unwrap();
+
+        // Entry b can't meet its budget with aPLib and keeps its LZSS data.
+        assert_eq!(rom.rom[0x00..0x02], [0xFF, 0xFF]);
+        assert_eq!(rom.rom[0x10..0x80], bytes[0x10..0x80]);
+        assert_eq!(rom.config.json()["assembly"]["a"]["format"], "ff6-aplib");
+        assert_eq!(rom.config.json()["assembly"]["b"]["format"], "ff6-lzss");
+
+        // Its LZSS data is over budget too, which is flagged along with the estimate.
+        assert!(!rom.records[0].over_budget);
+        assert!(rom.records[1].over_budget);
+        assert_eq!(rom.records[1].cycles, Some(10 * 40));
+
+        // Edited data has no LZSS stream to fall back to & is stored over budget with a warning.
+        let mut rom = fixture(test, bytes);
+        rom.edits.insert(("b".into(), None), vec![0x33; 10]);
+        let warnings = Arc::new(Mutex::new(Vec::new()));
+        let log = warnings.clone();
+        rom.set_listener(Some(Arc::new(move |event: &Event| {
+            if let Event::Warning { message, .. } = event
+            {
+                log.lock().unwrap().push(message.clone());
+            }
+        })));
+        rom.recompress("b").unwrap();
+        assert!(rom.records[0].over_budget);
+        assert!(warnings.lock().unwrap()[0].starts_with("over budget: "));
+        assert_eq!(rom.config.json()["assembly"]["b"]["format"], "ff6-aplib");
+    }
+
+    #[test]
+    fn recompress_range_error()
+    {
+        let test = r##"{ "assembly": { "a": { "name": "A", "range": "0xC00010-0xC00016" } } }"##;
+        let bytes = image(&[], &[(0x10, 0x11)]);
+        let mut rom = fixture(test, bytes.clone());
+        let data: Vec<u8> = (0..0x20).collect();
+        rom.edits.insert(("a".into(), None), data.clone());
+
+        // Data that outgrows the range of an entry that can't relocate would overwrite the next.
+        let len = aplib::compress(&data, &Profile::default()).unwrap().len();
+        let err = rom.recompress("a").unwrap_err();
+        let msg = format!(
+            "`a` (A): Size Error: `A` compressed size exceeds its range and it can't relocate \
+             ({}>6)",
+            len
+        );
+        assert_eq!(err.to_string(), msg);
+        assert_eq!(rom.rom, bytes);
+    }
+
+    #[test]
+    fn recompress_codecs()
+    {
+        let test = r##"
+    {
+        "assembly": {
+            "a": {
+                "name": "A",
+                "range": "0xC00000-0xC0000E",
+                "format": "ff6-lzss",
+                "codecs": ["ff6-aplib", "ff6-lzss"]
+            },
+            "b": {
+                "name": "B",
+                "range": "0xC00010-0xC0001E",
+                "format": "ff6-lzss",
+                "codecs": ["raw"]
+            },
+            "c": { "name": "C", "range": "0xC00020-0xC0002E", "format": "ff6-lzss" },
+            "d": {
+                "name": "D",
+                "range": "0xC00030-0xC0003E",
+                "format": "ff6-lzss",
+                "codecs": ["zip"]
+            }
+        }
+    }"##;
+        // Every entry is stored as LZSS literals only.
+        let literals = [0x0E, 0x00, 0xFF, 0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x11, 0x03];
+        let mut bytes = Vec::new();
+        for _ in 0..4
+        {
+            bytes.extend(literals);
+            bytes.extend([0x11, 0x11, 0x00, 0x00]);
+        }
+        bytes.resize(0x80, 0);
+        let mut rom = fixture(test, bytes);
+        rom.set_codecs(vec!["ff6-lzss".into(), "raw".into()]);
+        rom.set_verify(true);
+        rom.recompress("a").unwrap();
+        rom.recompress("b").unwrap();
+        rom.recompress("c").unwrap();
+
+        // The smallest stream is kept, with ties going to the codec listed first.
+        let aplib = aplib::compress(&[0x11; 10], &Profile::default()).unwrap().len();
+        let lzss = lzss::compress(&[0x11; 10]).unwrap().len();
+        let (codec, other) = match aplib <= lzss
+        {
+            | true => (Codec::Aplib, ("ff6-lzss".to_string(), lzss)),
+            | false => (Codec::Lzss, ("ff6-aplib:default".to_string(), aplib)),
+        };
+        let report = rom.report();
+        assert_eq!(report.records[0].codec, Some(codec));
+        assert_eq!(report.records[0].candidates, [other]);
+        assert_eq!(rom.config.json()["assembly"]["a"]["format"], codec.format());
+
+        // Raw data is only stored when the entry lists it itself.
+        assert_eq!(report.records[1].codec, Some(Codec::Raw));
+        assert_eq!(rom.rom[0x10..0x1A], [0x11; 10]);
+        assert_eq!(rom.config.json()["assembly"]["b"]["format"], "raw");
+        assert_eq!(report.records[2].codec, Some(Codec::Lzss));
+        assert_eq!(report.records[2].candidates, []);
+        assert!(report.records.iter().all(|r| r.verified));
+        assert_eq!(
+            rom.recompress("d").unwrap_err().to_string(),
+            "`d` (D): Codec Error: unsupported codec `zip` for this entry"
+        );
+
+        // Nothing is left to try when the only global codec needs listing by the entry.
+        rom.set_codecs(vec!["raw".into()]);
+        assert_eq!(
+            rom.recompress("c").unwrap_err().to_string(),
+            "`c` (C): Codec Error: unsupported codec `raw` for this entry"
+        );
+    }
+
+    #[test]
+    fn bench()
+    {
+        let test = r##"
+    {
+        "decoders": {
+            "ff6-lzss": { "routine": "0xC00100", "source": "0x00", "destination": "0x03" }
+        },
+        "assembly": {
+            "a": {
+                "name": "A",
+                "range": "0xC00010-0xC00030",
+                "arrayLength": 5,
+                "pointerTable": {
+                    "range": "0xC00000-0xC0000A",
+                    "offset": "0xC00000",
+                    "nullPointer": "0xFFFF"
+                }
+            }
+        }
+    }"##;
+        // Entry 1 aliases entry 0, entry 3 has no valid data & entry 4 is null.
+        let head = [0x10, 0x00, 0x10, 0x00, 0x16, 0x00, 0x1C, 0x00, 0xFF, 0xFF];
+        let mut bytes = image(&head, &[(0x10, 0x11), (0x16, 0x22)]);
+        bytes.resize(0x100, 0);
+
+        // "Decoder" that always writes 10 bytes of 0x11.
+        #[rustfmt::skip]
+        bytes.extend([
+            0xA9, 0x11, // LDA #$11
+            0xA0, 0x00, // LDY #$00
+            0x97, 0x03, // STA [$03],Y
+            0xC8,       // INY
+            0xC0, 0x0A, // CPY #$0A
+            0xD0, 0xF9, // BNE -7
+            0x6B,       // RTL
+        ]);
+        let rom = fixture(test, bytes);
+        let (benches, skipped) = rom.bench("a").unwrap();
+        #[rustfmt::skip]
+        assert_eq!(benches, [
+            Bench { index: 0, format: "ff6-lzss", size: 10, cycles: 139, matches: true },
+            Bench { index: 2, format: "ff6-lzss", size: 10, cycles: 139, matches: false },
+        ]);
+        let error = "LZSS Decompression Error: Invalid compression length of 0";
+        assert_eq!(skipped, [(3, error.to_string())]);
+    }
+
+    #[test]
+    fn export()
+    {
+        let test = r##"
+    {
+        "assembly": {
+            "a": {
+                "name": "A",
+                "range": "0xC00010-0xC0001C",
+                "arrayLength": 3,
+                "format": "ff6-lzss",
+                "pointerTable": { "range": "0xC00000-0xC00006", "offset": "0xC00000" }
+            },
+            "b": { "name": "B", "range": "0xC00020-0xC00028", "format": "ff6-aplib" },
+            "c": { "name": "C", "range": "0xC00030-0xC00040" }
+        }
+    }"##;
+        let mut bytes = image(&[0x10, 0x00, 0x40, 0x00, 0x16, 0x00], &[(0x10, 0x11), (0x16, 0x22)]);
+        bytes[0x20..0x28].copy_from_slice(&[0xFF, 0xFF, 0, 173, 1, 86, 192, 0]);
+        let rom = fixture(test, bytes);
+        let assets = rom.export().unwrap();
+
+        // The invalid pointer of entry 1 and the uncompressed entry are skipped.
+        let files: Vec<_> = assets.iter().map(|(a, _)| a.file()).collect();
+        assert_eq!(files, ["a/000.bin", "a/002.bin", "b.bin"]);
+        let (asset, data) = &assets[1];
+        assert_eq!(
+            (asset.addr, asset.codec, asset.size, asset.len),
+            (0xC00016, Codec::Lzss, 6, 10)
+        );
+        assert_eq!(data, &[0x22; 10]);
+        let (asset, data) = &assets[2];
+        assert_eq!(
+            (asset.addr, asset.codec, asset.size, asset.len),
+            (0xC00020, Codec::Aplib, 8, 100)
+        );
+        assert_eq!(asset.hash, crate::workspace::hash(data));
+    }
+
+    #[test]
+    fn export_dictionary()
+    {
+        let test = r##"
+    {
+        "assembly": {
+            "a": {
+                "name": "A",
+                "range": "0xC00010-0xC00040",
+                "arrayLength": 2,
+                "format": "ff6-lzss",
+                "pointerTable": {
+                    "range": "0xC00000-0xC00004",
+                    "offset": "0xC00000",
+                    "dictionary": "DICTIONARY",
+                    "dictionarySize": "0x0A"
+                }
+            }
+        }
+    }"##;
+        let data: Vec<u8> = (0..10).collect();
+        let stream = lzss::compress(&data).unwrap();
+        let mut bytes = vec![0x10, 0x00, 0x20, 0x00];
+        bytes.resize(0x80, 0);
+        bytes[0x10..0x10 + stream.len()].copy_from_slice(&stream);
+        bytes[0x20..0x20 + stream.len()].copy_from_slice(&stream);
+
+        // Entries compressed against a dictionary export as the data they were compressed from.
+        for dict in ["shared", "previous"]
+        {
+            let mut rom = fixture(&test.replace("DICTIONARY", dict), bytes.clone());
+            rom.recompress("a").unwrap();
+            let assets = rom.export().unwrap();
+            assert_eq!(assets.iter().map(|(_, d)| d).collect::<Vec<_>>(), [&data, &data]);
+        }
+    }
+
+    #[test]
+    fn recompress_relocate()
+    {
+        let test = r##"
+    {
+        "freeSpace": ["0xC00040-0xC00080"],
+        "assembly": {
+            "a": {
+                "name": "A",
+                "range": "0xC00010-0xC00016",
+                "canRelocate": true,
+                "reference": [{ "begin": "0xC00000", "mask": "0xFFFF" }]
+            }
+        }
+    }"##;
+        let bytes = image(&[0x10, 0x00], &[(0x10, 0x11)]);
+        let mut rom = fixture(test, bytes.clone());
+        rom.recompress("a").unwrap();
+
+        // The data outgrew its range & moved to free space, which now holds the original range.
+        let len = aplib::compress(&[0x11; 10], &Profile::default()).unwrap().len();
+        let end = format!("{:#08X}", 0xC00040 + len);
+        assert_eq!(rom.config.json()["assembly"]["a"]["range"], format!("0xC00040-{}", end));
+        let free = serde_json::json!(["0xC00010-0xC00016", format!("{}-0xC00080", end)]);
+        assert_eq!(rom.config.json()["freeSpace"], free);
+        assert_eq!(rom.rom[0x00..0x02], [0x40, 0x00]);
+
+        // Free space outside the bank is out of reach of the 16-bit reference.
+        let test = test.replace("0xC00040-0xC00080", "0xC10000-0xC10080");
+        let mut rom = fixture(&test, bytes);
+        let err = rom.recompress("a").unwrap_err();
+        let msg = format!(
+            "`a` (A): Relocation Error: no free space within reach for `A` ({} bytes)",
+            len
+        );
+        assert_eq!(err.to_string(), msg);
+    }
+
+    #[test]
+    fn recompress_relocate_table()
+    {
+        let test = r##"
+    {
+        "freeSpace": ["0xC00040-0xC00080"],
+        "assembly": {
+            "a": {
+                "name": "A",
+                "range": "0xC00010-0xC0001C",
+                "arrayLength": 2,
+                "canRelocate": true,
+                "pointerTable": { "range": "0xC00000-0xC00004", "offset": "0xC00000" }
+            }
+        }
+    }"##;
+        let mut rom =
+            fixture(test, image(&[0x10, 0x00, 0x16, 0x00], &[(0x10, 0x11), (0x16, 0x22)]));
+        rom.recompress("a").unwrap();
+
+        let len = aplib::compress(&[0x11; 10], &Profile::default()).unwrap().len();
+        let mut bank = TblBank::default();
+        let a = rom.config.extract("a").unwrap().table.unwrap();
+        assert_eq!(rom.extract_dp(&a, 0, &mut bank).unwrap(), 0x40);
+        assert_eq!(rom.extract_dp(&a, 1, &mut bank).unwrap(), 0x40 + len);
+        assert_eq!(rom.config.json()["freeSpace"][0], "0xC00010-0xC0001C");
+    }
+
+    #[test]
+    fn recompress_relocate_table_offset()
+    {
+        let test = r##"
+    {
+        "freeSpace": ["0xC10000-0xC10080"],
+        "assembly": {
+            "a": {
+                "name": "A",
+                "range": "0xC00010-0xC0001C",
+                "arrayLength": 2,
+                "format": "ff6-lzss",
+                "canRelocate": true,
+                "pointerTable": { "range": "0xC00000-0xC00004", "offset": "0xC00000" },
+                "reference": [
+                    { "begin": "0xC00020", "mask": "0xFF", "bankByte": true, "pointerOffset": true }
+                ]
+            }
+        }
+    }"##;
+        let mut bytes = image(&[0x10, 0x00, 0x16, 0x00], &[(0x10, 0x11), (0x16, 0x22)]);
+        bytes[0x20] = 0xC0;
+        bytes.resize(0x10080, 0);
+        let mut rom = fixture(test, bytes);
+        rom.recompress("a").unwrap();
+
+        // The data moved to the next bank, along with the table offset held by the code.
+        let len = aplib::compress(&[0x11; 10], &Profile::default()).unwrap().len();
+        assert_eq!(rom.config.json()["assembly"]["a"]["pointerTable"]["offset"], "0xC10000");
+        assert_eq!(rom.rom[0x20], 0xC1);
+        let mut bank = TblBank::default();
+        let a = rom.config.extract("a").unwrap().table.unwrap();
+        assert_eq!(rom.extract_dp(&a, 0, &mut bank).unwrap(), 0x00);
+        assert_eq!(rom.extract_dp(&a, 1, &mut bank).unwrap(), len);
+        let data: Vec<_> = rom.export().unwrap().into_iter().map(|(_, data)| data).collect();
+        assert_eq!(data, [[0x11; 10], [0x22; 10]]);
+    }
+
+    #[test]
+    fn rebuild()
+    {
+        let test = r##"
+    {
+        "freeSpace": ["0xC00040-0xC00080"],
+        "assembly": {
+            "a": {
+                "name": "A",
+                "range": "0xC00010-0xC00016",
+                "format": "ff6-lzss",
+                "canRelocate": true
+            },
+            "b": { "name": "B", "range": "0xC00020-0xC00026", "format": "ff6-lzss" }
+        }
+    }"##;
+        let bytes = image(&[], &[(0x10, 0x11), (0x20, 0x22)]);
+        let mut rom = fixture(test, bytes.clone());
+
+        // Export, edit a single asset, and rebuild.
+        let dir = tempfile::tempdir().expect("Failed to create temp directory.");
+        workspace::export(dir.path(), &rom.export().unwrap()).unwrap();
+        std::fs::write(dir.path().join("a.bin"), [0x33; 20]).unwrap();
+        assert_eq!(rom.import(dir.path()).unwrap(), ["a"]);
+        rom.rebuild(dir.path()).unwrap();
+
+        // The unchanged entry is left untouched.
+        let json = rom.config.json();
+        assert_eq!(json["assembly"]["b"]["format"], "ff6-lzss");
+        assert_eq!(rom.rom[0x20..0x26], bytes[0x20..0x26]);
+        assert_eq!(json["assembly"]["a"]["format"], "ff6-aplib");
+        let blob = rom.config.extract("a").unwrap();
+        let input = &rom.rom[conv_addr(blob.range.start)..];
+        assert_eq!(Codec::detect(input).decompress(input).unwrap(), [0x33; 20]);
+    }
+
+    #[test]
+    fn rebuild_table()
+    {
+        let test = r##"
+    {
+        "assembly": {
+            "a": {
+                "name": "A",
+                "range": "0xC00010-0xC00040",
+                "arrayLength": 2,
+                "format": "ff6-lzss",
+                "pointerTable": { "range": "0xC00000-0xC00004", "offset": "0xC00000" }
+            }
+        }
+    }"##;
+        let bytes = image(&[0x10, 0x00, 0x16, 0x00], &[(0x10, 0x11), (0x16, 0x22)]);
+        let dir = tempfile::tempdir().expect("Failed to create temp directory.");
+        workspace::export(dir.path(), &fixture(test, bytes.clone()).export().unwrap()).unwrap();
+        std::fs::write(dir.path().join("a/001.bin"), [0x33; 20]).unwrap();
+
+        // Only the edited index is recompressed, unless others are selected.
+        let mut rom = fixture(test, bytes.clone());
+        rom.rebuild(dir.path()).unwrap();
+        let codecs: Vec<_> = rom.records.iter().map(|r| (r.codec, r.time.is_zero())).collect();
+        assert_eq!(codecs, [(Some(Codec::Lzss), true), (Some(Codec::Aplib), false)]);
+
+        let mut rom = fixture(test, bytes);
+        rom.set_selector(Selector::new("a").unwrap());
+        rom.rebuild(dir.path()).unwrap();
+        let codecs: Vec<_> = rom.records.iter().map(|r| r.codec).collect();
+        assert_eq!(codecs, [Some(Codec::Aplib), Some(Codec::Aplib)]);
+    }
+
+    #[test]
+    fn extract_dp_auto_bank()
+    {
+        let rom = rom(vec![0x00, 0xFF, 0x10, 0x00, 0x20, 0x00]);
+        let tbl = PointerTable {
+            range: 0xC00000..0xC00006,
+            ptr_size: 2,
+            ptr_align: 0x10000,
+            auto_bank: true,
+            ..PointerTable::default()
+        };
+        let mut bank = TblBank::default();
+        assert_eq!(rom.extract_dp(&tbl, 0, &mut bank).unwrap(), 0x0FF00);
+        assert_eq!(rom.extract_dp(&tbl, 1, &mut bank).unwrap(), 0x10010);
+        assert_eq!(rom.extract_dp(&tbl, 2, &mut bank).unwrap(), 0x10020);
+    }
+
+    #[test]
+    fn splice_dp_auto_bank()
+    {
+        let mut rom = rom(vec![0; 4]);
+        let tbl = PointerTable {
+            range: 0xC00000..0xC00004,
+            ptr_size: 2,
+            ptr_align: 0x10000,
+            auto_bank: true,
+            ..PointerTable::default()
+        };
+        rom.splice_dp(&tbl, 1, 0x10010).unwrap();
+        assert_eq!(rom.rom, [0x00, 0x00, 0x10, 0x00]);
+    }
+
+    #[test]
+    fn bank_byte_table()
+    {
+        let mut rom = rom(vec![0x34, 0x12, 0x78, 0x56, 0xC1, 0xC2]);
+        let tbl = PointerTable {
+            range: 0xC00000..0xC00004,
+            ptr_size: 2,
+            ptr_align: 0x10000,
+            bank_byte: Some(0xC00004),
+            ..PointerTable::default()
+        };
+        let mut bank = TblBank::default();
+        assert_eq!(rom.extract_dp(&tbl, 1, &mut bank).unwrap(), 0xC25678);
+        rom.splice_dp(&tbl, 0, 0xC3ABCD).unwrap();
+        assert_eq!(rom.rom, [0xCD, 0xAB, 0x78, 0x56, 0xC3, 0xC2]);
+    }
+
+    #[test]
+    fn splice_dp_overflow_error()
+    {
+        let mut rom = rom(vec![0; 4]);
+        let tbl = PointerTable {
+            range: 0xC00000..0xC00004,
+            ptr_size: 2,
+            ptr_align: 0x10000,
+            ..PointerTable::default()
+        };
+        let err = rom.splice_dp(&tbl, 0, 0x10000).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Splice Pointer Error: pointer `0x10000` exceeds pointer range of `0x10000`"
+        );
+    }
+
+    #[test]
+    fn splice_ref()
+    {
+        let mut rom = rom(vec![0; 4]);
+        let r = Reference { addr: 0xC00000, mask: 0xFFFF, offset: -2, ..Reference::default() };
+        rom.splice_ref(&r, 0xD9D1B0).unwrap();
+        let r = Reference { addr: 0xC00002, mask: 0xFF, bank_byte: true, ..Reference::default() };
+        rom.splice_ref(&r, 0xD9D1B0).unwrap();
+        assert_eq!(rom.rom, [0xAE, 0xD1, 0xD9, 0x00]);
     }
 }
 
@@ -0,0 +1,177 @@
+use std::ops::Range;
+
+use crate::{error::SelectorError, result::Result};
+
+// Entry name glob along with the table indices it selects, e.g. 'mapLayouts[0..50]' or 'world*'.
+#[derive(Clone, Debug, PartialEq)]
+struct Pattern
+{
+    glob:    String,
+    indices: Option<Range<usize>>, // Every index when not given.
+}
+
+// Selection of entries & table indices to process, e.g.
+// 'mapLayouts[0..50],world*,!cinematicProgram'. Patterns prefixed with '!' exclude what they match,
+// and a selector of only exclusions selects everything else.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Selector
+{
+    include: Vec<Pattern>,
+    exclude: Vec<Pattern>,
+}
+
+impl Pattern
+{
+    fn new(pattern: &str) -> Result<Pattern>
+    {
+        let Some((glob, indices)) = pattern.strip_suffix(']').and_then(|p| p.split_once('['))
+        else
+        {
+            return match pattern.is_empty() || pattern.contains(['[', ']'])
+            {
+                | true => Err(SelectorError(pattern)),
+                | false => Ok(Pattern { glob: pattern.into(), indices: None }),
+            };
+        };
+
+        // Either a single index or an exclusive range of them.
+        let index = |s: &str| s.trim().parse::<usize>().map_err(|_| SelectorError(pattern));
+        let indices = match indices.split_once("..")
+        {
+            | Some((start, end)) => index(start)?..index(end)?,
+            | None => index(indices)?..index(indices)? + 1,
+        };
+        if glob.is_empty() || indices.is_empty()
+        {
+            return Err(SelectorError(pattern));
+        }
+        Ok(Pattern { glob: glob.into(), indices: Some(indices) })
+    }
+
+    fn matches(&self, field: &str) -> bool
+    {
+        glob(self.glob.as_bytes(), field.as_bytes())
+    }
+
+    fn contains(&self, field: &str, index: usize) -> bool
+    {
+        self.matches(field) && self.indices.as_ref().is_none_or(|r| r.contains(&index))
+    }
+}
+
+impl Selector
+{
+    pub fn new<S: AsRef<str>>(selector: S) -> Result<Selector>
+    {
+        let mut include = Vec::new();
+        let mut exclude = Vec::new();
+        for pattern in selector.as_ref().split(',').map(str::trim)
+        {
+            match pattern.strip_prefix('!')
+            {
+                | Some(pattern) => exclude.push(Pattern::new(pattern)?),
+                | None => include.push(Pattern::new(pattern)?),
+            }
+        }
+        Ok(Selector { include, exclude })
+    }
+
+    // Selection of nothing, e.g. when only edited entries are processed.
+    pub fn none() -> Selector
+    {
+        let all = Pattern { glob: "*".into(), indices: None };
+        Selector { include: Vec::new(), exclude: vec![all] }
+    }
+
+    // Check whether any index of an entry is selected. Entries excluded only in part are kept.
+    pub fn selects(&self, field: &str) -> bool
+    {
+        let included = self.include.is_empty() || self.include.iter().any(|p| p.matches(field));
+        included && !self.exclude.iter().any(|p| p.matches(field) && p.indices.is_none())
+    }
+
+    // Check whether a table index of an entry is selected.
+    pub fn selects_index(&self, field: &str, index: usize) -> bool
+    {
+        let included =
+            self.include.is_empty() || self.include.iter().any(|p| p.contains(field, index));
+        included && !self.exclude.iter().any(|p| p.contains(field, index))
+    }
+
+    // Filter entry names down to the selected ones, keeping their order.
+    pub fn fields<S: AsRef<str>>(&self, fields: &[S]) -> Vec<String>
+    {
+        let selected = fields.iter().map(AsRef::as_ref).filter(|f| self.selects(f));
+        selected.map(String::from).collect()
+    }
+}
+
+// Match a name against a glob where '*' matches any run of characters & '?' any single one.
+fn glob(pattern: &[u8], name: &[u8]) -> bool
+{
+    match (pattern.first(), name.first())
+    {
+        | (None, _) => name.is_empty(),
+        | (Some(b'*'), _) =>
+        {
+            glob(&pattern[1..], name) || (!name.is_empty() && glob(pattern, &name[1..]))
+        },
+        | (Some(b'?'), Some(_)) => glob(&pattern[1..], &name[1..]),
+        | (Some(p), Some(n)) if p == n => glob(&pattern[1..], &name[1..]),
+        | _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::Selector;
+
+    #[test]
+    fn glob()
+    {
+        assert!(super::glob(b"world*", b"worldLayout1"));
+        assert!(super::glob(b"*Layout?", b"worldLayout1"));
+        assert!(super::glob(b"*", b""));
+        assert!(!super::glob(b"world*", b"mapLayouts"));
+        assert!(!super::glob(b"mapLayout?", b"mapLayout"));
+    }
+
+    #[test]
+    fn selects()
+    {
+        let s =
+            Selector::new("mapLayouts[0..50], world*, !worldLayout2, !worldLayout1[3]").unwrap();
+        assert_eq!(
+            s.fields(&["mapLayouts", "mapTilesets", "worldLayout1", "worldLayout2"]),
+            ["mapLayouts", "worldLayout1"]
+        );
+        assert!(s.selects_index("mapLayouts", 49));
+        assert!(!s.selects_index("mapLayouts", 50));
+        assert!(s.selects_index("worldLayout1", 2));
+        assert!(!s.selects_index("worldLayout1", 3));
+
+        // Only exclusions select everything else.
+        let s = Selector::new("!cinematicProgram").unwrap();
+        assert_eq!(s.fields(&["cinematicProgram", "mapLayouts"]), ["mapLayouts"]);
+        assert!(s.selects_index("mapLayouts", 7));
+        let s = Selector::new("mapLayouts[7]").unwrap();
+        assert!(s.selects_index("mapLayouts", 7));
+        assert!(!s.selects_index("mapLayouts", 8));
+
+        // Nothing is selected by excluding everything.
+        let s = Selector::none();
+        assert_eq!(s.fields(&["cinematicProgram"]), Vec::<String>::new());
+        assert!(!s.selects_index("mapLayouts", 7));
+    }
+
+    #[test]
+    fn selector_error()
+    {
+        for selector in ["", "a,,b", "a[", "a[x]", "a[5..5]", "[0..2]", "a[0..2]b"]
+        {
+            let err = Selector::new(selector).unwrap_err();
+            assert!(err.to_string().starts_with("Selector Error: invalid entry selector"));
+        }
+    }
+}
@@ -0,0 +1,237 @@
+use crate::{
+    report::{Record, Report},
+    result::Result,
+    rom::conv_addr,
+};
+
+// Old & new address of an entry, or of a single index of a pointer table entry.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Relocation
+{
+    pub field:    String,
+    pub name:     String, // Display name of the entry, e.g. 'Map Layouts'.
+    pub index:    Option<usize>, // Table index, if the entry has a pointer table.
+    pub old_addr: usize,
+    pub new_addr: usize,
+    pub size:     usize, // Stored size (0 when shared or without data).
+}
+
+// Relocation map of a run, written as JSON & as debugger symbol files labelling the new addresses.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Map
+{
+    pub relocations: Vec<Relocation>,
+}
+
+impl Relocation
+{
+    fn new(record: &Record, name: String) -> Relocation
+    {
+        #[rustfmt::skip]
+        let relocation = Relocation {
+            field: record.field.clone(), name, index: record.index, old_addr: record.old_addr,
+            new_addr: record.new_addr, size: record.new_size,
+        };
+        relocation
+    }
+
+    // Symbol name from the display name & index, e.g. 'Map_Layouts_23'.
+    fn label(&self) -> String
+    {
+        let word = |c: char| match c.is_ascii_alphanumeric()
+        {
+            | true => c,
+            | false => '_',
+        };
+        let name: String = self.name.chars().map(word).collect();
+        match self.index
+        {
+            | Some(i) => format!("{}_{}", name, i),
+            | None => name,
+        }
+    }
+
+    fn to_json(&self) -> serde_json::Value
+    {
+        serde_json::json!({
+            "field": self.field,
+            "name": self.name,
+            "index": self.index,
+            "oldAddress": format!("{:#08X}", self.old_addr),
+            "newAddress": format!("{:#08X}", self.new_addr),
+            "size": self.size,
+        })
+    }
+}
+
+impl Map
+{
+    // Build the map of a run report, looking up the display name of every entry. Records without
+    // data of their own, e.g. null or empty table pointers, are skipped, unlike aliased ones.
+    pub fn new<F: Fn(&str) -> String>(report: &Report, name: F) -> Map
+    {
+        let records = report.records.iter().filter(|r| r.codec.is_some() || r.dedup.is_some());
+        let relocations = records.map(|r| Relocation::new(r, name(&r.field)));
+        Map { relocations: relocations.collect() }
+    }
+
+    pub fn to_json(&self) -> serde_json::Value
+    {
+        let relocations: Vec<_> = self.relocations.iter().map(Relocation::to_json).collect();
+        serde_json::json!({ "relocations": relocations })
+    }
+
+    // bsnes-plus symbols, e.g. 'c4:a000 Map_Layouts_23 ANY 96'.
+    pub fn to_bsnes(&self) -> String
+    {
+        let mut sym = String::from("#SNES65816\n\n[SYMBOL]\n");
+        for r in self.relocations.iter()
+        {
+            let (bank, addr) = (r.new_addr >> 16, r.new_addr & 0xFFFF);
+            sym += &format!("{:02x}:{:04x} {} ANY {}\n", bank, addr, r.label(), r.size.max(1));
+        }
+        sym
+    }
+
+    // Mesen labels by ROM offset, e.g. 'SnesPrgRom:04A000-04A05F:Map_Layouts_23'. Mesen keeps a
+    // single label per address, so labels of shared data go into the comment of its owner.
+    pub fn to_mesen(&self) -> String
+    {
+        let mut owners: Vec<(&Relocation, Vec<String>)> = Vec::new();
+        for r in self.relocations.iter()
+        {
+            match owners.iter_mut().find(|(owner, _)| owner.new_addr == r.new_addr)
+            {
+                | Some((_, shared)) => shared.push(r.label()),
+                | None => owners.push((r, Vec::new())),
+            }
+        }
+
+        let mut mlb = String::new();
+        for (r, shared) in owners.iter()
+        {
+            let offset = conv_addr(r.new_addr);
+            let range = match r.size
+            {
+                | 0 | 1 => format!("{:06X}", offset),
+                | size => format!("{:06X}-{:06X}", offset, offset + size - 1),
+            };
+            mlb += &format!("SnesPrgRom:{}:{}", range, r.label());
+            if !shared.is_empty()
+            {
+                mlb += &format!(":also {}", shared.join(", "));
+            }
+            mlb += "\n";
+        }
+        mlb
+    }
+
+    // WLA-DX symbols, e.g. 'c4:a000 Map_Layouts_23'.
+    pub fn to_wla(&self) -> String
+    {
+        let mut sym = String::from("; wla symbolic information file\n\n[labels]\n");
+        for r in self.relocations.iter()
+        {
+            sym += &format!("{:02x}:{:04x} {}\n", r.new_addr >> 16, r.new_addr & 0xFFFF, r.label());
+        }
+        sym
+    }
+
+    pub fn save<S: AsRef<str>>(&self, filename: S) -> Result<()>
+    {
+        std::fs::write(filename.as_ref(), serde_json::to_string_pretty(&self.to_json())?)?;
+        Ok(())
+    }
+
+    // Write the symbol files of every debugger next to each other, i.e. 'PREFIX.sym' for
+    // bsnes-plus, 'PREFIX.mlb' for Mesen & 'PREFIX.wla.sym' for WLA-DX.
+    pub fn save_symbols<S: AsRef<str>>(&self, prefix: S) -> Result<()>
+    {
+        let prefix = prefix.as_ref();
+        std::fs::write(format!("{}.sym", prefix), self.to_bsnes())?;
+        std::fs::write(format!("{}.mlb", prefix), self.to_mesen())?;
+        std::fs::write(format!("{}.wla.sym", prefix), self.to_wla())?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests
+{
+    use std::time::Duration;
+
+    use super::Map;
+    use crate::{
+        codec::Codec,
+        report::{Record, Report},
+    };
+
+    fn map() -> Map
+    {
+        #[rustfmt::skip]
+        let records = vec![
+            Record {
+                field: "a".into(), index: Some(0), old_addr: 0xC4A010, new_addr: 0xC4A000,
+                old_size: 6, new_size: 5, size: 10, codec: Some(Codec::Aplib), profile: None,
+                dedup: None,
+                verified: false, cycles: None, over_budget: false, time: Duration::ZERO,
+                candidates: Vec::new(),
+            },
+            Record {
+                field: "a".into(), index: Some(1), old_addr: 0xC4A016, new_addr: 0xC4A000,
+                old_size: 6, new_size: 0, size: 10, codec: Some(Codec::Aplib), profile: None,
+                dedup: Some(0xC4A000), verified: false, cycles: None, over_budget: false,
+                time: Duration::ZERO, candidates: Vec::new(),
+            },
+            Record {
+                field: "a".into(), index: Some(2), old_addr: 0xC4A01C, new_addr: 0xC4A005,
+                old_size: 0, new_size: 0, size: 0, codec: None, profile: None, dedup: None,
+                verified: false, cycles: None, over_budget: false, time: Duration::ZERO,
+                candidates: Vec::new(),
+            },
+            Record {
+                field: "b".into(), index: None, old_addr: 0xC50000, new_addr: 0xC50000,
+                old_size: 6, new_size: 1, size: 10, codec: Some(Codec::Lzss), profile: None,
+                dedup: None,
+                verified: false, cycles: None, over_budget: false, time: Duration::ZERO,
+                candidates: Vec::new(),
+            },
+        ];
+        let report = Report { records, ..Report::default() };
+        let names = [("a", "Map Layouts"), ("b", "B")];
+        Map::new(&report, |field| names.iter().find(|(f, _)| *f == field).unwrap().1.into())
+    }
+
+    #[test]
+    fn to_json()
+    {
+        let json = map().to_json();
+        assert_eq!(json["relocations"][0]["name"], "Map Layouts");
+        assert_eq!(json["relocations"][0]["oldAddress"], "0xC4A010");
+        assert_eq!(json["relocations"][1]["newAddress"], "0xC4A000");
+
+        // The empty pointer of a[2] has no data of its own & is skipped.
+        assert_eq!(json["relocations"].as_array().unwrap().len(), 3);
+        assert_eq!(json["relocations"][2]["index"], serde_json::Value::Null);
+    }
+
+    #[test]
+    fn symbols()
+    {
+        let map = map();
+        assert_eq!(
+            map.to_bsnes(),
+            "#SNES65816\n\n[SYMBOL]\nc4:a000 Map_Layouts_0 ANY 5\nc4:a000 Map_Layouts_1 ANY \
+             1\nc5:0000 B ANY 1\n"
+        );
+        assert_eq!(
+            map.to_mesen(),
+            "SnesPrgRom:04A000-04A004:Map_Layouts_0:also Map_Layouts_1\nSnesPrgRom:050000:B\n"
+        );
+        assert_eq!(
+            map.to_wla(),
+            "; wla symbolic information file\n\n[labels]\nc4:a000 Map_Layouts_0\nc4:a000 \
+             Map_Layouts_1\nc5:0000 B\n"
+        );
+    }
+}
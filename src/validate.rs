@@ -0,0 +1,301 @@
+use std::ops::Range;
+
+use serde_json::Value;
+
+use crate::{
+    error::{
+        Error, ValidateFreeSpaceError, ValidateHexError, ValidateOOBError, ValidateOverlapError,
+        ValidateRangeOrderError, ValidateTableOverlapError,
+    },
+    hex::HexStringTo,
+    json::Config,
+    rom::conv_addr,
+};
+
+// Keys that hold a single hex number when their value is a string.
+const HEX_KEYS: [&str; 9] = [
+    "offset",
+    "begin",
+    "mask",
+    "align",
+    "pointerAlign",
+    "length",
+    "window",
+    "maxOffset",
+    "maxSize",
+];
+
+struct Asset
+{
+    key:   String, // JSON key of the owning entry.
+    path:  String, // JSON path of the range string.
+    table: bool,   // Whether the range is a pointer table.
+    range: Range<usize>,
+}
+
+// Convert a SNES address range into a ROM offset range.
+fn rom_range(range: &Range<usize>) -> Range<usize>
+{
+    match range.start < range.end
+    {
+        | true => conv_addr(range.start)..conv_addr(range.end - 1) + 1,
+        | false => conv_addr(range.start)..conv_addr(range.start),
+    }
+}
+
+// Check whether a range string is in the decimal format of 'YYY-ZZZ'.
+fn is_decimal_range(s: &str) -> bool
+{
+    match s.split_once('-')
+    {
+        | Some((beg, end)) => beg.parse::<usize>().is_ok() && end.parse::<usize>().is_ok(),
+        | None => false,
+    }
+}
+
+// Recursively check every hex string in the definition and report malformed ones.
+fn check_hex(json: &Value, path: &str, errors: &mut Vec<Error>)
+{
+    match json
+    {
+        | Value::Object(map) =>
+        {
+            for (key, value) in map
+            {
+                let path = format!("{}/{}", path, key);
+                if let Some(s) = value.as_str()
+                {
+                    // Plain decimal strings are also valid, e.g. '0-16'.
+                    let result = match key.as_str()
+                    {
+                        | "range" if !is_decimal_range(s) => s.hex_to_range::<usize>().map(|_| ()),
+                        | key if HEX_KEYS.contains(&key) && s.parse::<usize>().is_err() =>
+                        {
+                            s.hex_to::<usize>().map(|_| ())
+                        },
+                        | _ => Ok(()),
+                    };
+                    if let Err(e) = result
+                    {
+                        errors.push(ValidateHexError(path, e.to_string()));
+                    }
+                }
+                else
+                {
+                    check_hex(value, &path, errors);
+                }
+            }
+        },
+        | Value::Array(arr) =>
+        {
+            for (i, value) in arr.iter().enumerate()
+            {
+                check_hex(value, &format!("{}/{}", path, i), errors);
+            }
+        },
+        | _ => (),
+    }
+}
+
+// Parse an absolute range, check its order and bounds, and convert it into ROM offsets.
+fn parse_range(s: &str, path: &str, rom_len: usize, errors: &mut Vec<Error>)
+    -> Option<Range<usize>>
+{
+    // Malformed strings have already been reported by check_hex.
+    let range = s.hex_to_range::<usize>().ok()?;
+
+    if range.start > range.end
+    {
+        errors.push(ValidateRangeOrderError(path));
+        return None;
+    }
+
+    let range = rom_range(&range);
+    if range.end > rom_len
+    {
+        errors.push(ValidateOOBError(path.to_string(), format!("{:#X}", rom_len)));
+    }
+    Some(range)
+}
+
+pub fn validate(config: &Config) -> Vec<Error>
+{
+    let json = config.json();
+    let mut errors = Vec::new();
+
+    // Check hex strings first so that later checks can skip malformed ranges.
+    check_hex(json, "", &mut errors);
+
+    // Decode ROM length, e.g. '0x300000'. Skip bounds checks if it is missing.
+    let rom_len = json["length"].as_str().and_then(|s| s.hex_to().ok()).unwrap_or(usize::MAX);
+
+    // Gather the absolute ranges of every top-level entry and its pointer table.
+    let mut assets = Vec::new();
+    for (key, entry) in json["assembly"].as_object().into_iter().flatten()
+    {
+        let ranges = [
+            (format!("/assembly/{}/range", key), &entry["range"], false),
+            (
+                format!("/assembly/{}/pointerTable/range", key),
+                &entry["pointerTable"]["range"],
+                true,
+            ),
+        ];
+        for (path, range, table) in ranges
+        {
+            if let Some(range) =
+                range.as_str().and_then(|s| parse_range(s, &path, rom_len, &mut errors))
+            {
+                assets.push(Asset { key: key.to_string(), path, table, range });
+            }
+        }
+    }
+
+    // Sort by start and sweep for overlapping neighbours.
+    assets.sort_by_key(|a| (a.range.start, a.range.end));
+    for (i, a) in assets.iter().enumerate()
+    {
+        for b in assets[i + 1..].iter().take_while(|b| b.range.start < a.range.end)
+        {
+            if b.range.is_empty()
+            {
+                continue;
+            }
+            match a.key == b.key && a.table != b.table
+            {
+                | true => errors.push(ValidateTableOverlapError(&a.key)),
+                | false => errors.push(ValidateOverlapError(&a.path, &b.path)),
+            }
+        }
+    }
+
+    // Free space must stay clear of every asset.
+    for (i, free) in json["freeSpace"].as_array().into_iter().flatten().enumerate()
+    {
+        let path = format!("/freeSpace/{}", i);
+        if let Some(range) = free.as_str().and_then(|s| parse_range(s, &path, rom_len, &mut errors))
+        {
+            for a in
+                assets.iter().filter(|a| a.range.start < range.end && range.start < a.range.end)
+            {
+                errors.push(ValidateFreeSpaceError(&path, &a.path));
+            }
+        }
+    }
+
+    errors
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::validate;
+    use crate::json::Config;
+
+    fn messages(test: &str) -> Vec<String>
+    {
+        let config = Config::new(test).unwrap();
+        validate(&config).iter().map(|e| e.to_string()).collect()
+    }
+
+    #[test]
+    fn validate_default()
+    {
+        let config = Config::default();
+        let errors = validate(&config).iter().map(|e| e.to_string()).collect::<Vec<_>>();
+        assert_eq!(errors, [
+            "Validation Error: free space `/freeSpace/31` overlaps `/assembly/attackGraphics3bpp/range`"
+        ]);
+    }
+
+    #[test]
+    fn validate_overlap()
+    {
+        let test = r##"
+    {
+        "length": "0x300000",
+        "assembly": {
+            "a": { "range": "0xC00000-0xC00010" },
+            "b": { "range": "0xC0000F-0xC00020" },
+            "c": { "range": "0xC00020-0xC00030" }
+        }
+    }"##;
+        assert_eq!(
+            messages(test),
+            ["Validation Error: range `/assembly/a/range` overlaps `/assembly/b/range`"]
+        );
+    }
+
+    #[test]
+    fn validate_table_overlap()
+    {
+        let test = r##"
+    {
+        "length": "0x300000",
+        "assembly": {
+            "a": {
+                "range": "0xC00000-0xC00010",
+                "pointerTable": { "range": "0xC0000E-0xC00012" }
+            }
+        }
+    }"##;
+        assert_eq!(messages(test), ["Validation Error: pointer table `a` overlaps its data"]);
+    }
+
+    #[test]
+    fn validate_free_space()
+    {
+        let test = r##"
+    {
+        "length": "0x300000",
+        "assembly": {
+            "a": { "range": "0xC00000-0xC00010" }
+        },
+        "freeSpace": ["0xC00010-0xC00020", "0xC00008-0xC00018"]
+    }"##;
+        assert_eq!(
+            messages(test),
+            ["Validation Error: free space `/freeSpace/1` overlaps `/assembly/a/range`"]
+        );
+    }
+
+    #[test]
+    fn validate_out_of_bounds()
+    {
+        let test = r##"
+    {
+        "length": "0x300000",
+        "assembly": {
+            "a": { "range": "0xEFFFF0-0xF00010" }
+        },
+        "freeSpace": ["0xF00010-0xF00020"]
+    }"##;
+        assert_eq!(
+            messages(test),
+            [
+                "Validation Error: range `/assembly/a/range` extends past end of ROM (>0x300000)",
+                "Validation Error: range `/freeSpace/0` extends past end of ROM (>0x300000)",
+            ]
+        );
+    }
+
+    #[test]
+    fn validate_malformed_hex()
+    {
+        let test = r##"
+    {
+        "length": "0x300000",
+        "assembly": {
+            "a": {
+                "range": "0xC00000",
+                "pointerTable": { "range": "0xC00020-0xC00010", "offset": "C00000" }
+            }
+        }
+    }"##;
+        assert_eq!(messages(test), [
+            "Validation Error: `/assembly/a/pointerTable/offset`: Error Parsing: invalid hex string `C00000`",
+            "Validation Error: `/assembly/a/range`: Error Parsing: invalid hex string range `0xC00000`",
+            "Validation Error: range `/assembly/a/pointerTable/range` ends before it begins",
+        ]);
+    }
+}
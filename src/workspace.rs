@@ -0,0 +1,176 @@
+use std::{fs, path::Path};
+
+use crate::{codec::Codec, error::JsonError, hex::HexStringTo, result::Result, JsonError};
+
+// Name of the manifest file within a workspace directory.
+pub const MANIFEST: &str = "manifest.json";
+
+// Decompressed asset of a definition entry, or of a single index of a pointer table entry.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Asset
+{
+    pub field: String,
+    pub index: Option<usize>, // Table index, if the entry has a pointer table.
+    pub addr:  usize,         // Original address of the compressed data.
+    pub codec: Codec,
+    pub size:  usize, // Original compressed size.
+    pub len:   usize, // Decompressed size.
+    pub hash:  u64,   // Hash of the decompressed data.
+}
+
+impl Asset
+{
+    // Path of the asset within the workspace, e.g. 'mapLayouts/023.bin'.
+    pub fn file(&self) -> String
+    {
+        match self.index
+        {
+            | Some(i) => format!("{}/{:03}.bin", self.field, i),
+            | None => format!("{}.bin", self.field),
+        }
+    }
+
+    fn to_json(&self) -> serde_json::Value
+    {
+        serde_json::json!({
+            "field": self.field,
+            "index": self.index,
+            "file": self.file(),
+            "address": format!("{:#08X}", self.addr),
+            "codec": self.codec.format(),
+            "compressedSize": self.size,
+            "size": self.len,
+            "hash": format!("{:016x}", self.hash),
+        })
+    }
+
+    fn from_json(j_asset: &serde_json::Value, path: &str) -> Result<Asset>
+    {
+        let field = j_asset["field"].as_str().ok_or(JsonError!("{}/field", path))?.to_string();
+        let index = j_asset["index"].as_u64().map(|i| i as usize);
+        let addr = j_asset["address"].as_str().ok_or(JsonError!("{}/address", path))?.hex_to()?;
+        let codec = match j_asset["codec"].as_str()
+        {
+            | Some(format) if format == Codec::Lzss.format() => Codec::Lzss,
+            | Some(format) if format == Codec::Aplib.format() => Codec::Aplib,
+            | _ => return Err(JsonError!("{}/codec", path)),
+        };
+        let number = |key: &str| j_asset[key].as_u64().ok_or(JsonError!("{}/{}", path, key));
+        let size = number("compressedSize")? as usize;
+        let len = number("size")? as usize;
+        let hash = j_asset["hash"]
+            .as_str()
+            .and_then(|s| u64::from_str_radix(s, 16).ok())
+            .ok_or(JsonError!("{}/hash", path))?;
+        Ok(Asset { field, index, addr, codec, size, len, hash })
+    }
+}
+
+// Index of every asset exported to a workspace.
+#[derive(Debug, Default, PartialEq)]
+pub struct Manifest
+{
+    pub assets: Vec<Asset>,
+}
+
+impl Manifest
+{
+    pub fn to_json(&self) -> serde_json::Value
+    {
+        serde_json::json!({ "assets": self.assets.iter().map(Asset::to_json).collect::<Vec<_>>() })
+    }
+
+    pub fn from_json(json: &serde_json::Value) -> Result<Manifest>
+    {
+        let j_assets = json["assets"].as_array().ok_or(JsonError("/assets"))?;
+        let mut assets = Vec::new();
+        for (i, j_asset) in j_assets.iter().enumerate()
+        {
+            assets.push(Asset::from_json(j_asset, &format!("/assets/{}", i))?);
+        }
+        Ok(Manifest { assets })
+    }
+
+    pub fn load<P: AsRef<Path>>(dir: P) -> Result<Manifest>
+    {
+        let json = serde_json::from_str(&fs::read_to_string(dir.as_ref().join(MANIFEST))?)?;
+        Manifest::from_json(&json)
+    }
+}
+
+// 64-bit FNV-1a hash. Stable across platforms & toolchains, unlike the std hasher.
+pub fn hash(data: &[u8]) -> u64
+{
+    data.iter().fold(0xCBF29CE484222325, |h, &b| (h ^ b as u64).wrapping_mul(0x100000001B3))
+}
+
+// Write every asset to its file within the workspace along with the manifest.
+pub fn export<P: AsRef<Path>>(dir: P, assets: &[(Asset, Vec<u8>)]) -> Result<Manifest>
+{
+    let dir = dir.as_ref();
+    for (asset, data) in assets
+    {
+        let file = dir.join(asset.file());
+        if let Some(parent) = file.parent()
+        {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(file, data)?;
+    }
+
+    let manifest = Manifest { assets: assets.iter().map(|(asset, _)| asset.clone()).collect() };
+    fs::create_dir_all(dir)?;
+    fs::write(dir.join(MANIFEST), serde_json::to_string_pretty(&manifest.to_json())?)?;
+    Ok(manifest)
+}
+
+#[cfg(test)]
+mod tests
+{
+    use super::{Asset, Manifest};
+    use crate::codec::Codec;
+
+    fn asset(index: Option<usize>) -> Asset
+    {
+        #[rustfmt::skip]
+        let asset = Asset {
+            field: "a".into(), index, addr: 0xC00010, codec: Codec::Lzss, size: 6, len: 10,
+            hash: super::hash(&[0x11; 10]),
+        };
+        asset
+    }
+
+    #[test]
+    fn hash()
+    {
+        assert_eq!(super::hash(&[]), 0xCBF29CE484222325);
+        assert_eq!(super::hash(b"a"), 0xAF63DC4C8601EC8C);
+    }
+
+    #[test]
+    fn file()
+    {
+        assert_eq!(asset(None).file(), "a.bin");
+        assert_eq!(asset(Some(23)).file(), "a/023.bin");
+    }
+
+    #[test]
+    fn export()
+    {
+        let dir = tempfile::tempdir().expect("Failed to create temp directory.");
+        let assets = [(asset(None), vec![0x11; 10]), (asset(Some(1)), vec![0x11; 10])];
+        let manifest = super::export(dir.path(), &assets).unwrap();
+
+        assert_eq!(std::fs::read(dir.path().join("a/001.bin")).unwrap(), [0x11; 10]);
+        assert_eq!(manifest.to_json()["assets"][1]["address"], "0xC00010");
+        assert_eq!(Manifest::load(dir.path()).unwrap(), manifest);
+    }
+
+    #[test]
+    fn manifest_error()
+    {
+        let json = serde_json::json!({ "assets": [{ "field": "a", "address": "0xC00010" }] });
+        let err = Manifest::from_json(&json).unwrap_err();
+        assert_eq!(err.to_string(), "Error Parsing: failed to find JSON entry `/assets/0/codec`");
+    }
+}